@@ -1,9 +1,11 @@
+use crate::api::cache::{read_cache, write_cache};
+use crate::api::key::{KeySource, api_get_key};
 use crate::api::request::*;
-use home::home_dir;
-use std::io;
-use std::result::Result::Ok;
+use crate::datetime::LocationZone;
+use chrono::{DateTime, Utc};
+use std::time::Duration;
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Clone, Copy, Debug)]
 pub enum CurrentCondition {
     Rain,
     Snow,
@@ -15,75 +17,189 @@ pub enum CurrentCondition {
     Unknown,
 }
 
+impl CurrentCondition {
+    /// a stable lowercase name, used in machine-readable output
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Rain => "rain",
+            Self::Snow => "snow",
+            Self::Sun => "sun",
+            Self::Cloud => "cloud",
+            Self::Clear => "clear",
+            Self::Fog => "fog",
+            Self::Thunder => "thunder",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    /// a one-character symbol for single-line output
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Self::Rain => "🌧",
+            Self::Snow => "❄",
+            Self::Sun => "☀",
+            Self::Cloud => "☁",
+            Self::Clear => "☾",
+            Self::Fog => "🌫",
+            Self::Thunder => "⛈",
+            Self::Unknown => "?",
+        }
+    }
+}
+
+/// weather data providers regn knows how to query
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Provider {
+    WeatherApi,
+}
+
+impl Provider {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "weatherapi" => Some(Self::WeatherApi),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::WeatherApi => "weatherapi",
+        }
+    }
+
+    /// the name as the provider writes it, for people to read
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::WeatherApi => "WeatherAPI",
+        }
+    }
+}
+
 pub struct WeatherAPI {
     pub location: RespLocation,
+    pub zone: LocationZone,
+    /// the moment the location's local time was reported
+    pub now: DateTime<Utc>,
+    /// when the data was fetched, earlier than now if it came from the cache
+    pub fetched_at: DateTime<Utc>,
     pub current_condition: CurrentCondition,
     pub current_condition_as_str: String,
     pub current_temp_c: f64,
+    pub current_feelslike_c: f64,
+    pub current_wind_kph: f64,
+    pub current_precip_mm: f64,
+    pub current_pressure_mb: f64,
+    pub current_humidity: f64,
     pub forecast_days: Vec<RespForecastDay>,
+    pub alerts: Vec<RespAlert>,
 }
 
 /// this is what gets called from main.rs
-pub fn api_main(location: &String, forecast: &i32) -> WeatherAPI {
-    let local_key = api_get_local_key();
+pub fn api_main(
+    provider: &Provider,
+    key_source: &KeySource,
+    location: &String,
+    forecast: &i32,
+    max_age: Duration,
+) -> Result<WeatherAPI, String> {
+    let cache_key = format!("{}|{}|{}", provider.name(), location, forecast);
+    let cached = read_cache(&cache_key, max_age).and_then(|(body, at)| {
+        serde_json::from_str::<WeatherResponse>(&body)
+            .ok()
+            .map(|r| (r, at))
+    });
+
+    let (r, fetched_at) = match cached {
+        Some(c) => c,
+        None => {
+            let local_key = api_get_key(provider, key_source)?;
+            let body = match provider {
+                Provider::WeatherApi => api_request(local_key, location.to_string(), forecast)
+                    .map_err(|e| format!("Failed to query WeatherAPI ({}). Please check your network connection.", e.without_url()))?,
+            };
+            let r = parse_response(&body)?;
+            write_cache(&cache_key, &body);
+            (r, Utc::now())
+        }
+    };
 
-    let r = api_request(local_key, location.to_string(), forecast)
-        .map_err(|_| "Failed to query WeatherAPI. Please check that your API key is valid.")
-        .unwrap();
+    let zone = LocationZone::new(
+        &r.location.tz_id,
+        r.location.localtime,
+        r.location.localtime_epoch,
+    );
+    let now = DateTime::from_timestamp(r.location.localtime_epoch, 0)
+        .unwrap_or(Utc::now());
 
-    return WeatherAPI {
+    Ok(WeatherAPI {
         location: r.location,
+        zone,
+        now,
+        fetched_at,
         current_condition: parse_current_weather(r.current.condition.text.clone()),
         current_condition_as_str: r.current.condition.text,
         current_temp_c: r.current.temp_c,
+        current_feelslike_c: r.current.feelslike_c,
+        current_wind_kph: r.current.wind_kph,
+        current_precip_mm: r.current.precip_mm,
+        current_pressure_mb: r.current.pressure_mb,
+        current_humidity: r.current.humidity,
         forecast_days: r.forecast.forecastday,
-    };
+        alerts: r.alerts.alert,
+    })
+}
+
+/// the provider answers errors with a JSON body of its own
+fn parse_response(body: &str) -> Result<WeatherResponse, String> {
+    serde_json::from_str::<WeatherResponse>(body).map_err(|e| {
+        match serde_json::from_str::<RespError>(body) {
+            Ok(err) => format!(
+                "WeatherAPI: {} Please check that your API key and location are valid.",
+                err.error.message
+            ),
+            Err(_) => format!("Could not read the answer from WeatherAPI ({e})."),
+        }
+    })
 }
 
-/// helper: api_get_local_key
-fn api_read_local_file() -> io::Result<String> {
-    let h: String = home_dir().unwrap().display().to_string();
-    let d: String = format!("{}/.regn", h);
-    std::fs::read_to_string(d)
+/// looks up locations matching the query
+pub fn search_main(
+    provider: &Provider,
+    key_source: &KeySource,
+    query: &str,
+) -> Result<Vec<RespSearchLocation>, String> {
+    let local_key = api_get_key(provider, key_source)?;
+
+    match provider {
+        Provider::WeatherApi => api_search(local_key, query.to_string())
+            .map_err(|e| format!("Failed to search WeatherAPI ({e}). Please check that your API key is valid.")),
+    }
 }
 
-fn api_get_local_key() -> String {
-    match api_read_local_file() {
-        Ok(key) => {
-            let k: String = key.trim().to_string();
-            if k.is_empty() {
-                panic!(
-                "ERROR: No WeatherAPI key was supplied in \"~/.regn\". Please add your accuweather key."
-            )
-            } else {
-                return k;
-            }
-        }
-        _ => {
-            panic!(
-            "ERROR: \".regn\" does not exist in your home directory. Create this file (\"~/.regn\") and supply your accuweather WeatherAPI key inside it."
-        )
-        }
-    };
+/// checks a key against the provider before it is saved
+pub fn check_key_main(provider: &Provider, key: &str) -> Result<KeyCheck, String> {
+    match provider {
+        Provider::WeatherApi => api_check_key(key.to_string()),
+    }
 }
 
-/// parses the current weather description from the API response
-fn parse_current_weather(current: String) -> CurrentCondition {
+/// parses a weather description from the API response
+pub fn parse_current_weather(current: String) -> CurrentCondition {
     let c: &str = current.as_str().trim();
     let cl = c.to_lowercase();
 
     match cl {
-        s if s.contains("sun") => return CurrentCondition::Sun,
-        s if s.contains("cloud") => return CurrentCondition::Cloud,
-        s if s.contains("snow") | s.contains("blizzard") => return CurrentCondition::Snow,
-        s if s.contains("rain") | s.contains("pour") => return CurrentCondition::Rain,
-        s if s.contains("clear") => return CurrentCondition::Clear,
+        s if s.contains("sun") => CurrentCondition::Sun,
+        s if s.contains("cloud") => CurrentCondition::Cloud,
+        s if s.contains("snow") | s.contains("blizzard") => CurrentCondition::Snow,
+        s if s.contains("rain") | s.contains("pour") => CurrentCondition::Rain,
+        s if s.contains("clear") => CurrentCondition::Clear,
         s if s.contains("mist") | s.contains("fog") | s.contains("overcast") => {
-            return CurrentCondition::Fog;
+            CurrentCondition::Fog
         }
         s if s.contains("storm") | s.contains("thunder") => {
-            return CurrentCondition::Thunder;
+            CurrentCondition::Thunder
         }
-        _ => return CurrentCondition::Unknown,
+        _ => CurrentCondition::Unknown,
     }
 }
@@ -0,0 +1,60 @@
+use std::{
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::config::cache_dir;
+
+/// one file per provider, location and number of days
+fn cache_path(key: &str) -> PathBuf {
+    cache_dir().join(format!("{:016x}.json", fnv1a(&key.to_lowercase())))
+}
+
+/// 64-bit FNV-1a. unlike std's hasher it gives the same value with
+/// every Rust release, so upgrading doesn't throw the cache away.
+fn fnv1a(s: &str) -> u64 {
+    s.bytes().fold(0xcbf29ce484222325, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// a response body saved less than `max_age` ago, and when it was fetched
+pub fn read_cache(key: &str, max_age: Duration) -> Option<(String, DateTime<Utc>)> {
+    if max_age.is_zero() {
+        return None;
+    }
+    let path = cache_path(key);
+    let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+    let age = SystemTime::now().duration_since(modified).unwrap_or_default();
+    if age > max_age {
+        return None;
+    }
+    let body = std::fs::read_to_string(&path).ok()?;
+    Some((body, DateTime::<Utc>::from(modified)))
+}
+
+/// failing to write the cache only means the next run fetches again.
+/// the body is renamed into place so a bar polling at the same time
+/// never reads half a file.
+pub fn write_cache(key: &str, body: &str) {
+    let path = cache_path(key);
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".{}.tmp", std::process::id()));
+    let _ = std::fs::create_dir_all(cache_dir())
+        .and_then(|_| std::fs::write(&tmp, body))
+        .and_then(|_| std::fs::rename(&tmp, &path));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stable_hash() {
+        assert_eq!(fnv1a(""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a("a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a("foobar"), 0x85944171f73967e8);
+    }
+}
@@ -0,0 +1,102 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use home::home_dir;
+use serde::Deserialize;
+
+use crate::api::api_main::Provider;
+
+/// where to look for a provider's API key, from the config file
+#[derive(Deserialize, Default, PartialEq, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct KeySource {
+    /// shell command that prints the key, e.g. "pass show weatherapi"
+    pub key_command: Option<String>,
+    /// file containing only the key
+    pub key_file: Option<PathBuf>,
+}
+
+/// looks for the key in this order:
+/// $REGN_<PROVIDER>_API_KEY, $REGN_API_KEY, key_command, key_file, ~/.regn
+pub fn api_get_key(provider: &Provider, src: &KeySource) -> Result<String, String> {
+    let provider_var = format!("REGN_{}_API_KEY", provider.name().to_uppercase());
+    for var in [provider_var.as_str(), "REGN_API_KEY"] {
+        if let Ok(k) = std::env::var(var)
+            && !k.trim().is_empty()
+        {
+            return Ok(k.trim().to_string());
+        }
+    }
+
+    if let Some(cmd) = &src.key_command {
+        return key_from_command(cmd);
+    }
+
+    if let Some(path) = &src.key_file {
+        return key_from_file(path);
+    }
+
+    let legacy = home_dir().map(|h| h.join(".regn")).ok_or(
+        "Could not find your home directory. Set $REGN_API_KEY or add a key_command to your config file.",
+    )?;
+    if !legacy.exists() {
+        return Err(format!(
+            "No {p} API key was found. Run \"regn init\", set $REGN_API_KEY, add a key_command or key_file under [keys.{p}] in your config file, or supply your key in \"~/.regn\".",
+            p = provider.name()
+        ));
+    }
+    key_from_file(&legacy)
+}
+
+pub fn key_from_command(cmd: &str) -> Result<String, String> {
+    #[cfg(windows)]
+    let out = Command::new("cmd").args(["/C", cmd]).output();
+    #[cfg(not(windows))]
+    let out = Command::new("sh").args(["-c", cmd]).output();
+
+    let out = out.map_err(|e| format!("Could not run key_command \"{cmd}\": {e}"))?;
+    if !out.status.success() {
+        return Err(format!(
+            "key_command \"{cmd}\" failed ({}): {}",
+            out.status,
+            String::from_utf8_lossy(&out.stderr).trim()
+        ));
+    }
+    // only the first line, like `pass` prints the password first
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let k = stdout.lines().next().unwrap_or("").trim().to_string();
+    if k.is_empty() {
+        return Err(format!("key_command \"{cmd}\" did not print a key."));
+    }
+    Ok(k)
+}
+
+fn key_from_file(path: &Path) -> Result<String, String> {
+    let key = std::fs::read_to_string(path)
+        .map_err(|e| format!("Could not read \"{}\": {e}", path.display()))?;
+    warn_if_readable_by_others(path);
+    let k = key.trim().to_string();
+    if k.is_empty() {
+        return Err(format!("No API key was supplied in \"{}\".", path.display()));
+    }
+    Ok(k)
+}
+
+#[cfg(unix)]
+fn warn_if_readable_by_others(path: &Path) {
+    use std::os::unix::fs::PermissionsExt;
+
+    if let Ok(meta) = std::fs::metadata(path)
+        && meta.permissions().mode() & 0o077 != 0
+    {
+        eprintln!(
+            "WARNING: \"{p}\" is accessible by other users. Restrict it with: chmod 600 {p}",
+            p = path.display()
+        );
+    }
+}
+
+#[cfg(not(unix))]
+fn warn_if_readable_by_others(_path: &Path) {}
@@ -1,2 +1,4 @@
 pub mod api_main;
+pub mod cache;
+pub mod key;
 pub mod request;
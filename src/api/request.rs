@@ -1,24 +1,38 @@
-use reqwest::Error;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use reqwest::{Error, Url};
 use serde::Deserialize;
 use std::result::Result::Ok;
 
+use crate::datetime::{de_astro_time, de_local_datetime};
+
 #[derive(Debug, Deserialize)]
 pub struct WeatherResponse {
     pub location: RespLocation,
     pub current: RespCurrent,
     pub forecast: RespForecast,
+    /// missing from responses cached before alerts were asked for
+    #[serde(default)]
+    pub alerts: RespAlerts,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct RespLocation {
     pub name: String,
     pub country: String,
-    pub localtime: String,
+    #[serde(deserialize_with = "de_local_datetime")]
+    pub localtime: NaiveDateTime,
+    pub localtime_epoch: i64,
+    pub tz_id: String,
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct RespCurrent {
     pub temp_c: f64,
+    pub feelslike_c: f64,
+    pub wind_kph: f64,
+    pub precip_mm: f64,
+    pub pressure_mb: f64,
+    pub humidity: f64,
     pub condition: RespCondition,
 }
 
@@ -29,14 +43,50 @@ pub struct RespForecast {
 
 #[derive(Debug, Deserialize)]
 pub struct RespForecastDay {
-    pub date: String,
+    pub date: NaiveDate,
     pub day: RespDay,
+    pub astro: RespAstro,
+    pub hour: Vec<RespHour>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct RespDay {
     pub maxtemp_c: f64,
     pub mintemp_c: f64,
+    pub maxwind_kph: f64,
+    pub totalprecip_mm: f64,
+    #[serde(default)]
+    pub totalsnow_cm: f64,
+    pub daily_chance_of_rain: i32,
+    #[serde(default)]
+    pub uv: f64,
+    pub condition: RespCondition,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RespAstro {
+    #[serde(deserialize_with = "de_astro_time")]
+    pub sunrise: Option<NaiveTime>,
+    #[serde(deserialize_with = "de_astro_time")]
+    pub sunset: Option<NaiveTime>,
+    #[serde(default, deserialize_with = "de_astro_time")]
+    pub moonrise: Option<NaiveTime>,
+    #[serde(default, deserialize_with = "de_astro_time")]
+    pub moonset: Option<NaiveTime>,
+    #[serde(default)]
+    pub moon_phase: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RespHour {
+    pub time_epoch: i64,
+    pub temp_c: f64,
+    pub feelslike_c: f64,
+    pub wind_kph: f64,
+    pub precip_mm: f64,
+    pub chance_of_rain: i32,
+    #[serde(default)]
+    pub uv: f64,
     pub condition: RespCondition,
 }
 
@@ -45,16 +95,158 @@ pub struct RespCondition {
     pub text: String,
 }
 
+#[derive(Debug, Deserialize, Default)]
+pub struct RespAlerts {
+    #[serde(default)]
+    pub alert: Vec<RespAlert>,
+}
+
+/// a weather warning issued for the location. times are RFC 3339.
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct RespAlert {
+    pub headline: String,
+    pub severity: String,
+    pub event: String,
+    pub areas: String,
+    pub effective: String,
+    pub expires: String,
+    pub desc: String,
+    pub instruction: String,
+}
+
+/// one match from the search/autocomplete endpoint
+#[derive(Debug, Deserialize, Clone)]
+pub struct RespSearchLocation {
+    pub name: String,
+    pub region: String,
+    pub country: String,
+    pub lat: f64,
+    pub lon: f64,
+}
+
+const API_BASE: &str = "http://api.weatherapi.com/v1";
+
+/// an endpoint with its query parameters percent-encoded, so a
+/// location like "A&B" stays one parameter
+fn endpoint(path: &str, params: &[(&str, &str)]) -> Url {
+    Url::parse_with_params(&format!("{API_BASE}/{path}"), params)
+        .expect("the API base URL is valid")
+}
+
+/// the raw response body, so it can be cached as is
 #[tokio::main]
 pub async fn api_request(
     local_key: String,
     location: String,
     forecast: &i32,
-) -> Result<WeatherResponse, Error> {
-    let url = format!(
-        "http://api.weatherapi.com/v1/forecast.json?key={}&q={}&days={}&aqi=no&alerts=no",
-        local_key, location, forecast
+) -> Result<String, Error> {
+    let url = endpoint(
+        "forecast.json",
+        &[
+            ("key", &local_key),
+            ("q", &location),
+            ("days", &forecast.to_string()),
+            ("aqi", "no"),
+            ("alerts", "yes"),
+        ],
     );
-    let r = reqwest::get(&url).await?.json::<WeatherResponse>().await?;
+    let r = reqwest::get(url).await?.text().await?;
     Ok(r)
 }
+
+/// errors leave out the URL, since it holds the key
+#[tokio::main]
+pub async fn api_search(
+    local_key: String,
+    query: String,
+) -> Result<Vec<RespSearchLocation>, String> {
+    let url = endpoint("search.json", &[("key", &local_key), ("q", &query)]);
+    let r = reqwest::get(url)
+        .await
+        .map_err(|e| e.without_url().to_string())?;
+
+    if !r.status().is_success() {
+        let status = r.status();
+        return Err(match r.json::<RespError>().await {
+            Ok(e) => format!("{} (code {})", e.error.message, e.error.code),
+            Err(_) => format!("answered {status}"),
+        });
+    }
+    r.json::<Vec<RespSearchLocation>>()
+        .await
+        .map_err(|e| format!("unexpected answer: {}", e.without_url()))
+}
+
+/// error body returned with 4xx responses
+#[derive(Debug, Deserialize)]
+pub struct RespError {
+    pub error: RespErrorBody,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RespErrorBody {
+    pub code: i32,
+    pub message: String,
+}
+
+/// result of a successful key check
+#[derive(Debug)]
+pub struct KeyCheck {
+    pub location: String,
+    /// any plan, quota or rate limit headers the provider sent back
+    pub quota: Vec<(String, String)>,
+}
+
+/// makes a minimal request to see whether the key is accepted
+#[tokio::main]
+pub async fn api_check_key(local_key: String) -> Result<KeyCheck, String> {
+    let url = endpoint("current.json", &[("key", &local_key), ("q", "London"), ("aqi", "no")]);
+    let r = reqwest::get(url)
+        .await
+        .map_err(|e| format!("Could not reach WeatherAPI: {}", e.without_url()))?;
+
+    let quota = r
+        .headers()
+        .iter()
+        .filter(|(k, _)| {
+            let k = k.as_str();
+            k.contains("quota") || k.contains("ratelimit") || k.starts_with("x-weatherapi")
+        })
+        .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
+        .collect();
+
+    if !r.status().is_success() {
+        let status = r.status();
+        return Err(match r.json::<RespError>().await {
+            Ok(e) => format!("{} (code {})", e.error.message, e.error.code),
+            Err(_) => format!("WeatherAPI answered {status}"),
+        });
+    }
+
+    #[derive(Deserialize)]
+    struct Resp {
+        location: RespSearchLocation,
+    }
+    let body = r
+        .json::<Resp>()
+        .await
+        .map_err(|e| format!("Unexpected answer from WeatherAPI: {}", e.without_url()))?;
+    Ok(KeyCheck {
+        location: format!("{}, {}", body.location.name, body.location.country),
+        quota,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_is_encoded() {
+        let url = endpoint("search.json", &[("key", "k"), ("q", "A&B #1+2")]);
+        assert_eq!(url.as_str(), "http://api.weatherapi.com/v1/search.json?key=k&q=A%26B+%231%2B2");
+        let q: Vec<_> = url.query_pairs().collect();
+        assert_eq!(q[1].1, "A&B #1+2");
+    }
+}
@@ -1,55 +1,1174 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    net::SocketAddr,
+    path::PathBuf,
+    time::Duration,
+};
+
+use crate::{
+    api::{api_main::Provider, key::KeySource},
+    bar::Bar,
+    completions::Shell,
+    config::{Settings, load_config},
+    datetime::{Clock, DisplayZone, TimeFormat, is_valid_date_format, is_valid_time_format},
+    output::{ColorChoice, OutputFormat},
+    places::{PlacesCmd, resolve_location},
+    report::ReportFormat,
+    serve::parse_listen_addr,
+    template::Template,
+    theme::Theme,
+    units::{PrecipUnit, PressureUnit, TempUnit, UnitSystem, Units, WindUnit},
+};
+
+/// what regn was asked to do
+#[derive(PartialEq, Clone, Debug)]
+pub enum Command {
+    Tui,
+    Now,
+    Forecast,
+    Hourly,
+    Search(String),
+    Config(ConfigCmd),
+    Places(PlacesCmd),
+    Init,
+    Export,
+    Report,
+    Ics,
+    Completions(Shell),
+    /// hidden, prints the locations offered when completing -l
+    CompleteLocations,
+    Man,
+}
+
+/// `regn config ...`
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum ConfigCmd {
+    Show,
+    Path,
+}
+
 #[derive(PartialEq, Clone)]
 pub struct Arguments {
+    pub command: Command,
     pub no_tui: bool,
     pub location: String,
     pub help: bool,
+    /// the command that help was asked for, if one was named
+    pub help_topic: Option<&'static CmdSpec>,
+    pub version: bool,
     pub forecast: i32,
+    pub hours: i32,
+    pub units: Units,
+    pub time_format: TimeFormat,
+    pub time_zone: DisplayZone,
+    pub provider: Provider,
+    pub theme: Theme,
+    pub color: ColorChoice,
+    pub fps: f64,
+    pub animations: bool,
+    /// temperature and rain charts under the forecast
+    pub charts: bool,
+    /// the braille temperature graph at the bottom of the TUI
+    pub graph: bool,
+    pub key_source: KeySource,
+    pub output: OutputFormat,
+    /// how old a cached response may be and still be used
+    pub max_age: Duration,
+    /// rows per hour in table output
+    pub hourly: bool,
+    pub openmetrics: bool,
+    /// --markdown or --html, for `regn report`
+    pub report: Option<ReportFormat>,
+    /// --output, a file to write instead of stdout
+    pub out_path: Option<PathBuf>,
+    /// --serve, where to listen for HTTP requests
+    pub serve: Option<SocketAddr>,
+    pub profile: Option<String>,
+    pub places: BTreeMap<String, String>,
+    /// the location as given with -l, remembered for completion
+    pub typed_location: Option<String>,
 }
 
+pub const DEF_LOCATION: &str = "Stockholm";
 const DEF_FORECAST: i32 = 5;
+const MIN_FORECAST: i32 = 1;
 const MAX_FORECAST: i32 = 10;
+const DEF_HOURS: i32 = 24;
+const DEF_FPS: f64 = 60.0;
+const MIN_FPS: f64 = 1.0;
+const MAX_FPS: f64 = 240.0;
+/// minutes, for --bar and --format
+const DEF_STATUS_MAX_AGE: u64 = 10;
+
+/// a command line option. `value` is the placeholder shown in help
+/// for options that take a value.
+#[derive(PartialEq, Debug)]
+pub struct OptSpec {
+    pub short: Option<char>,
+    pub long: &'static str,
+    pub value: Option<&'static str>,
+    /// accepted values, offered by shell completion
+    pub choices: &'static [&'static str],
+    pub help: &'static str,
+}
+
+/// a subcommand and the options it accepts. `-h` and `-V` are accepted
+/// everywhere.
+#[derive(PartialEq, Debug)]
+pub struct CmdSpec {
+    pub name: &'static str,
+    pub args: &'static str,
+    pub about: &'static str,
+    pub options: &'static [&'static [&'static str]],
+}
+
+pub const OPTIONS: &[OptSpec] = &[
+    OptSpec {
+        short: Some('l'),
+        long: "location",
+        value: Some("str"),
+        choices: &[],
+        help: "choose city location, \"lat,lon\" or a saved place as @name (default: Stockholm. Cities with spaces must be enclosed with double quotes; refer to the example down below!)",
+    },
+    OptSpec {
+        short: Some('t'),
+        long: "text",
+        value: None,
+        choices: &[],
+        help: "view result directly in stdout instead of a TUI",
+    },
+    OptSpec {
+        short: None,
+        long: "json",
+        value: None,
+        choices: &[],
+        help: "print the weather as JSON, in metric units (implies --text)",
+    },
+    OptSpec {
+        short: None,
+        long: "json-pretty",
+        value: None,
+        choices: &[],
+        help: "like --json, indented for reading",
+    },
+    OptSpec {
+        short: None,
+        long: "csv",
+        value: None,
+        choices: &[],
+        help: "print one comma separated row per day, with a header (implies --text)",
+    },
+    OptSpec {
+        short: None,
+        long: "tsv",
+        value: None,
+        choices: &[],
+        help: "like --csv, separated by tabs",
+    },
+    OptSpec {
+        short: None,
+        long: "format",
+        value: Some("str"),
+        choices: &[],
+        help: "print one line from a template, e.g. \"{icon} {temp}° {cond} ↑{max} ↓{min}\" (see Templates below)",
+    },
+    OptSpec {
+        short: None,
+        long: "bar",
+        value: Some("str"),
+        choices: &["waybar", "i3blocks", "polybar"],
+        help: "print for a status bar (waybar, i3blocks, polybar), using --format as the text if given",
+    },
+    OptSpec {
+        short: None,
+        long: "max-age",
+        value: Some("int"),
+        choices: &[],
+        help: "reuse a cached answer younger than this many minutes (default: 10 with --bar, --format, export and ics, else 0)",
+    },
+    OptSpec {
+        short: None,
+        long: "hourly",
+        value: None,
+        choices: &[],
+        help: "one row per hour instead of per day in --csv and --tsv output",
+    },
+    OptSpec {
+        short: None,
+        long: "openmetrics",
+        value: None,
+        choices: &[],
+        help: "export in the OpenMetrics text format, for Prometheus",
+    },
+    OptSpec {
+        short: None,
+        long: "markdown",
+        value: None,
+        choices: &[],
+        help: "write the report as Markdown",
+    },
+    OptSpec {
+        short: None,
+        long: "html",
+        value: None,
+        choices: &[],
+        help: "write the report as a standalone HTML page",
+    },
+    OptSpec {
+        short: Some('o'),
+        long: "output",
+        value: Some("path"),
+        choices: &[],
+        help: "write to a file instead of stdout, e.g. for node_exporter's textfile collector",
+    },
+    OptSpec {
+        short: None,
+        long: "serve",
+        value: Some("addr"),
+        choices: &[],
+        help: "serve over HTTP instead of printing, e.g. --serve :9101",
+    },
+    OptSpec {
+        short: Some('f'),
+        long: "days",
+        value: Some("int"),
+        choices: &[],
+        help: "set number of days to forecast (1 to 10. default: 5)",
+    },
+    OptSpec {
+        short: None,
+        long: "hours",
+        value: Some("int"),
+        choices: &[],
+        help: "number of hours to show, starting from now (default: 24)",
+    },
+    OptSpec {
+        short: None,
+        long: "units",
+        value: Some("str"),
+        choices: &["metric", "imperial", "uk"],
+        help: "unit system to display (metric, imperial or uk. default: metric)",
+    },
+    OptSpec {
+        short: None,
+        long: "temp",
+        value: Some("str"),
+        choices: &["c", "f"],
+        help: "override temperature unit (c, f)",
+    },
+    OptSpec {
+        short: None,
+        long: "wind",
+        value: Some("str"),
+        choices: &["kmh", "ms", "mph", "kn"],
+        help: "override wind speed unit (kmh, ms, mph, kn)",
+    },
+    OptSpec {
+        short: None,
+        long: "precip",
+        value: Some("str"),
+        choices: &["mm", "in"],
+        help: "override precipitation unit (mm, in)",
+    },
+    OptSpec {
+        short: None,
+        long: "pressure",
+        value: Some("str"),
+        choices: &["hpa", "inhg"],
+        help: "override pressure unit (hpa, inhg)",
+    },
+    OptSpec {
+        short: None,
+        long: "date-format",
+        value: Some("str"),
+        choices: &[],
+        help: "strftime format used for dates (default: \"%a %d %b\", e.g. \"Mon 19 Oct\")",
+    },
+    OptSpec {
+        short: None,
+        long: "time-format",
+        value: Some("str"),
+        choices: &[],
+        help: "strftime format used for times (overrides --clock)",
+    },
+    OptSpec {
+        short: None,
+        long: "clock",
+        value: Some("int"),
+        choices: &["12", "24"],
+        help: "12 or 24-hour clock (default: 24)",
+    },
+    OptSpec {
+        short: None,
+        long: "tz",
+        value: Some("str"),
+        choices: &["location", "local", "utc"],
+        help: "timezone for sunrise, sunset and hourly times (location, local, utc or an IANA name such as \"Asia/Tokyo\". default: location)",
+    },
+    OptSpec {
+        short: None,
+        long: "theme",
+        value: Some("str"),
+        choices: &["default", "mono", "ocean"],
+        help: "colour theme (default, mono, ocean. default: default)",
+    },
+    OptSpec {
+        short: None,
+        long: "color",
+        value: Some("str"),
+        choices: &["auto", "always", "never"],
+        help: "colours in text output (auto, always, never. default: auto, off when not a terminal or $NO_COLOR is set)",
+    },
+    OptSpec {
+        short: None,
+        long: "chart",
+        value: None,
+        choices: &[],
+        help: "draw temperature and chance of rain charts under the forecast",
+    },
+    OptSpec {
+        short: None,
+        long: "graph",
+        value: None,
+        choices: &[],
+        help: "start the TUI with the temperature graph shown (toggle with g)",
+    },
+    OptSpec {
+        short: None,
+        long: "no-anim",
+        value: None,
+        choices: &[],
+        help: "disable the weather animations",
+    },
+    OptSpec {
+        short: None,
+        long: "profile",
+        value: Some("str"),
+        choices: &[],
+        help: "use a named profile from the config file",
+    },
+    OptSpec {
+        short: Some('h'),
+        long: "help",
+        value: None,
+        choices: &[],
+        help: "print help",
+    },
+    OptSpec {
+        short: Some('V'),
+        long: "version",
+        value: None,
+        choices: &[],
+        help: "print version",
+    },
+];
+
+const DISPLAY_OPTS: &[&str] = &[
+    "units",
+    "temp",
+    "wind",
+    "precip",
+    "pressure",
+    "date-format",
+    "time-format",
+    "clock",
+    "tz",
+    "color",
+    "profile",
+];
+
+/// machine-readable output, for the commands that print weather
+const OUTPUT_OPTS: &[&str] = &[
+    "json",
+    "json-pretty",
+    "csv",
+    "tsv",
+    "format",
+    "bar",
+    "max-age",
+    "hourly",
+];
+
+pub const COMMANDS: &[CmdSpec] = &[
+    CmdSpec {
+        name: "tui",
+        args: "",
+        about: "show the forecast in an animated TUI (default)",
+        options: &[&["location", "days", "text", "theme", "chart", "graph", "no-anim"], DISPLAY_OPTS, OUTPUT_OPTS],
+    },
+    CmdSpec {
+        name: "now",
+        args: "",
+        about: "print the current conditions",
+        options: &[&["location"], DISPLAY_OPTS, OUTPUT_OPTS],
+    },
+    CmdSpec {
+        name: "forecast",
+        args: "",
+        about: "print the daily forecast",
+        options: &[&["location", "days"], DISPLAY_OPTS, OUTPUT_OPTS],
+    },
+    CmdSpec {
+        name: "hourly",
+        args: "",
+        about: "print the hourly forecast",
+        options: &[&["location", "days", "hours"], DISPLAY_OPTS, OUTPUT_OPTS],
+    },
+    CmdSpec {
+        name: "export",
+        args: "",
+        about: "export the weather for monitoring (--openmetrics)",
+        options: &[
+            &["location", "days", "profile", "max-age"],
+            &["openmetrics", "output", "serve"],
+        ],
+    },
+    CmdSpec {
+        name: "report",
+        args: "",
+        about: "write a forecast report for sharing (--markdown or --html)",
+        options: &[
+            &["location", "days", "max-age", "markdown", "html", "output"],
+            DISPLAY_OPTS,
+        ],
+    },
+    CmdSpec {
+        name: "ics",
+        args: "",
+        about: "write the forecast as an iCalendar feed, one all-day event per day",
+        options: &[&["location", "days", "max-age", "output", "serve"], DISPLAY_OPTS],
+    },
+    CmdSpec {
+        name: "search",
+        args: "<str>",
+        about: "look up locations matching a name",
+        options: &[&["profile"]],
+    },
+    CmdSpec {
+        name: "config",
+        args: "[show|path]",
+        about: "print the active settings as TOML, or the config file path",
+        options: &[DISPLAY_OPTS, &["location", "days", "theme", "chart", "graph", "no-anim"]],
+    },
+    CmdSpec {
+        name: "places",
+        args: "[list|add <name> <location>|remove <name>]",
+        about: "manage saved places, use them with -l @name",
+        options: &[],
+    },
+    CmdSpec {
+        name: "init",
+        args: "",
+        about: "interactive first-run setup of key, location and units",
+        options: &[],
+    },
+    CmdSpec {
+        name: "completions",
+        args: "<bash|zsh|fish>",
+        about: "print a shell completion script",
+        options: &[],
+    },
+    CmdSpec {
+        name: "man",
+        args: "",
+        about: "print the man page",
+        options: &[],
+    },
+    CmdSpec {
+        name: "help",
+        args: "[command]",
+        about: "print help, or help for a command",
+        options: &[],
+    },
+];
+
+impl CmdSpec {
+    pub fn find(name: &str) -> Option<&'static CmdSpec> {
+        COMMANDS.iter().find(|c| c.name == name)
+    }
+
+    pub fn accepts(&self, long: &str) -> bool {
+        matches!(long, "help" | "version")
+            || self.options.iter().any(|g| g.contains(&long))
+    }
+
+    /// the options of this command, in the order of OPTIONS
+    pub fn option_specs(&self) -> impl Iterator<Item = &'static OptSpec> {
+        OPTIONS.iter().filter(|o| self.accepts(o.long))
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub enum ArgError {
+    UnknownFlag { flag: String, suggestion: Option<String> },
+    UnexpectedArg { arg: String, suggestion: Option<String> },
+    MissingValue { flag: String },
+    UnexpectedValue { flag: String },
+    NotAccepted { flag: String, command: &'static str },
+    ExtraArg { arg: String, command: &'static str },
+    InvalidValue { name: String, value: String, expected: String },
+    /// a command used the wrong way, with a line on how to use it
+    Usage(String),
+    /// the config file is invalid, or names a missing profile or place
+    Other(String),
+}
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownFlag { flag, suggestion } => {
+                write!(f, "unknown flag \"{flag}\"")?;
+                if let Some(s) = suggestion {
+                    write!(f, ", did you mean \"{s}\"?")?;
+                }
+                Ok(())
+            }
+            Self::UnexpectedArg { arg, suggestion } => {
+                write!(f, "unexpected argument \"{arg}\"")?;
+                match suggestion {
+                    Some(s) => write!(f, ", did you mean \"{s}\"?"),
+                    // most likely a city name with spaces
+                    None => write!(
+                        f,
+                        " (locations with spaces must be quoted: -l \"rio de janeiro\")"
+                    ),
+                }
+            }
+            Self::MissingValue { flag } => {
+                write!(f, "no value was given after the \"{flag}\" flag")
+            }
+            Self::UnexpectedValue { flag } => {
+                write!(f, "the \"{flag}\" flag does not take a value")
+            }
+            Self::NotAccepted { flag, command } => {
+                write!(
+                    f,
+                    "the \"{flag}\" flag cannot be used with \"{command}\" (see: regn help {command})"
+                )
+            }
+            Self::ExtraArg { arg, command } => {
+                write!(
+                    f,
+                    "unexpected argument \"{arg}\" for \"{command}\" (see: regn help {command})"
+                )
+            }
+            Self::InvalidValue { name, value, expected } => {
+                write!(f, "\"{value}\" is not a valid value for \"{name}\" (expected {expected})")
+            }
+            Self::Usage(msg) | Self::Other(msg) => write!(f, "{msg}"),
+        }
+    }
+}
 
-pub fn parse_args() -> Arguments {
-    let mut it = std::env::args().skip(1); // skip program name
+impl ArgError {
+    /// 2 for usage errors, like most command line tools
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Other(_) => 1,
+            _ => 2,
+        }
+    }
+}
+
+/// command line flags take precedence over the chosen profile,
+/// which takes precedence over the top level of the config file
+pub fn parse_args(
+    args: impl Iterator<Item = String>,
+) -> Result<Arguments, ArgError> {
+    let mut it = args.peekable();
     let mut no_tui = false;
-    let mut location = String::from("Stockholm");
     let mut help = false;
-    let mut forecast = DEF_FORECAST;
+    let mut version = false;
+    let mut hours = DEF_HOURS;
+    let mut output = OutputFormat::Text;
+    let mut hourly = false;
+    let mut bar: Option<Bar> = None;
+    let mut openmetrics = false;
+    let mut report = None;
+    let mut out_path: Option<PathBuf> = None;
+    let mut serve: Option<SocketAddr> = None;
+    let mut profile: Option<String> = None;
+    let mut cli = Settings::default();
+    let mut cmd: Option<&'static CmdSpec> = None;
+    let mut positionals: Vec<String> = Vec::new();
+    let mut places_cmd: Option<PlacesCmd> = None;
+    // flags are checked against the command once it is known,
+    // since they may come before it
+    let mut used: Vec<(&'static OptSpec, String)> = Vec::new();
     while let Some(arg) = it.next() {
-        match arg.as_str() {
-            "-l" => {
-                location = it
-                    .next()
-                    .expect("No location was given after the \"-l\" flag.");
+        let (spec, inline) = match arg.strip_prefix("--") {
+            Some(long) => {
+                let (name, inline) = match long.split_once('=') {
+                    Some((n, v)) => (n, Some(v.to_string())),
+                    None => (long, None),
+                };
+                match OPTIONS.iter().find(|o| o.long == name) {
+                    Some(spec) => (spec, inline),
+                    None => {
+                        return Err(ArgError::UnknownFlag {
+                            suggestion: suggest(name, OPTIONS.iter().map(|o| o.long))
+                                .map(|s| format!("--{s}")),
+                            flag: arg,
+                        });
+                    }
+                }
             }
-            "help" => {
-                help = true;
+            None if arg.starts_with('-') && arg.len() > 1 => {
+                let mut chars = arg.chars().skip(1);
+                let short = chars.next();
+                // -f5 is the same as -f 5
+                let rest: String = chars.collect();
+                let inline = (!rest.is_empty()).then_some(rest);
+                match OPTIONS.iter().find(|o| o.short.is_some() && o.short == short) {
+                    Some(spec) => (spec, inline),
+                    None => {
+                        return Err(ArgError::UnknownFlag {
+                            flag: arg,
+                            suggestion: None,
+                        });
+                    }
+                }
             }
-            "-t" => {
-                no_tui = true;
+            None => {
+                match cmd {
+                    // "regn help forecast"
+                    Some(c) if c.name == "help" && positionals.is_empty() => {
+                        match CmdSpec::find(&arg) {
+                            Some(topic) => cmd = Some(topic),
+                            None => positionals.push(arg),
+                        }
+                    }
+                    Some(c) if c.name == "places" => {
+                        // places has its own small grammar
+                        let rest = std::iter::once(arg).chain(&mut it);
+                        places_cmd =
+                            Some(PlacesCmd::parse(rest).map_err(ArgError::Usage)?);
+                    }
+                    Some(_) => positionals.push(arg),
+                    None => match CmdSpec::find(&arg) {
+                        Some(c) => {
+                            help |= c.name == "help";
+                            cmd = Some(c);
+                        }
+                        None => {
+                            return Err(ArgError::UnexpectedArg {
+                                suggestion: suggest(
+                                    &arg,
+                                    COMMANDS.iter().map(|c| c.name),
+                                )
+                                .map(str::to_string),
+                                arg,
+                            });
+                        }
+                    },
+                }
+                continue;
             }
-            "-f" => {
-                // use next if some and parse to i32, else default
-                forecast = it
-                    .next()
-                    .as_deref()
-                    .unwrap_or(format!("{}", DEF_FORECAST).as_str())
-                    .parse::<i32>()
-                    .unwrap_or(DEF_FORECAST);
+        };
+
+        let flag = display_flag(spec);
+        let value = match (spec.value, inline) {
+            (Some(_), Some(v)) => v,
+            (Some(_), None) => {
+                // "-l -t" means the location was forgotten, but "-f -3"
+                // is a (bad) value
+                let next_is_flag = it.peek().is_some_and(|n| is_known_flag(n));
+                match it.next_if(|_| !next_is_flag) {
+                    Some(v) => v,
+                    None => return Err(ArgError::MissingValue { flag }),
+                }
             }
+            (None, Some(_)) => return Err(ArgError::UnexpectedValue { flag }),
+            (None, None) => String::new(),
+        };
 
-            _ => {}
+        match spec.long {
+            "location" => cli.location = Some(value),
+            "text" => no_tui = true,
+            "json" => output = OutputFormat::Json,
+            "json-pretty" => output = OutputFormat::JsonPretty,
+            "csv" => output = OutputFormat::Csv,
+            "tsv" => output = OutputFormat::Tsv,
+            "format" => {
+                let t = Template::parse(&value)
+                    .map_err(|e| ArgError::Usage(format!("Invalid {flag} template: {e}")))?;
+                if t.days_needed() > MAX_FORECAST as usize {
+                    return Err(invalid(
+                        &flag,
+                        &value,
+                        &format!("day numbers from 0 to {}", MAX_FORECAST - 1),
+                    ));
+                }
+                output = OutputFormat::Template(t);
+            }
+            "bar" => {
+                bar = Some(parse_value(&value, &flag, "waybar, i3blocks or polybar", Bar::parse)?)
+            }
+            "max-age" => {
+                cli.max_age = Some(parse_value(&value, &flag, "a whole number of minutes", |v| {
+                    v.parse::<u64>().ok()
+                })?)
+            }
+            "hourly" => hourly = true,
+            "openmetrics" => openmetrics = true,
+            "markdown" => report = Some(ReportFormat::Markdown),
+            "html" => report = Some(ReportFormat::Html),
+            "output" => out_path = Some(PathBuf::from(value)),
+            "serve" => {
+                serve = Some(parse_value(&value, &flag, "an address such as :9101", parse_listen_addr)?)
+            }
+            "days" => cli.forecast_days = Some(parse_days(&value, &flag)?),
+            "hours" => hours = parse_hours(&value, &flag)?,
+            "units" => cli.units = Some(value),
+            "temp" => cli.temp = Some(value),
+            "wind" => cli.wind = Some(value),
+            "precip" => cli.precip = Some(value),
+            "pressure" => cli.pressure = Some(value),
+            "date-format" => cli.date_format = Some(value),
+            "time-format" => cli.time_format = Some(value),
+            "clock" => cli.clock = Some(value),
+            "tz" => cli.tz = Some(value),
+            "theme" => cli.theme = Some(value),
+            "color" => cli.color = Some(value),
+            "chart" => cli.charts = Some(true),
+            "graph" => cli.graph = Some(true),
+            "no-anim" => cli.animations = Some(false),
+            "profile" => profile = Some(value),
+            "help" => help = true,
+            "version" => version = true,
+            _ => unreachable!("option \"{}\" is not handled", spec.long),
+        }
+        used.push((spec, flag));
+    }
+
+    let spec = match cmd {
+        Some(c) if c.name != "help" => c,
+        _ => CmdSpec::find("tui").unwrap_or(&COMMANDS[0]),
+    };
+    let help_topic = cmd.filter(|c| c.name != "help");
+    // flags are not checked when only asking for help
+    if !help
+        && let Some((_, flag)) = used.iter().find(|(o, _)| !spec.accepts(o.long))
+    {
+        return Err(ArgError::NotAccepted {
+            flag: flag.clone(),
+            command: spec.name,
+        });
+    }
+
+    let command = if help || version {
+        Command::Tui
+    } else {
+        command_from(spec, positionals, places_cmd)?
+    };
+    if command == Command::Report && report.is_none() {
+        return Err(ArgError::Usage(
+            "Choose a report format: regn report --markdown or --html".to_string(),
+        ));
+    }
+
+    // saved places are already offered by completion on their own
+    let typed_location = cli.location.clone().filter(|l| !l.starts_with('@'));
+
+    let mut config = load_config().map_err(ArgError::Other)?;
+    let keys = std::mem::take(&mut config.keys);
+    let places = std::mem::take(&mut config.places);
+    let settings = config
+        .resolve(profile.as_deref())
+        .map_err(ArgError::Other)?
+        .overlay(cli);
+
+    let max_age = settings.max_age;
+    let mut args = resolve_settings(settings, keys, no_tui, help)?;
+    // a broken default place must not block `regn places` from fixing it
+    let needs_location = matches!(
+        command,
+        Command::Tui
+            | Command::Now
+            | Command::Forecast
+            | Command::Hourly
+            | Command::Export
+            | Command::Report
+            | Command::Ics
+    );
+    if needs_location && !help && !version {
+        args.location = resolve_location(args.location, &places)
+            .map_err(ArgError::Other)?;
+    }
+    args.command = command;
+    args.help_topic = help_topic;
+    args.version = version;
+    args.hours = hours;
+    // --format is the bar's text when both are given
+    if let Some(bar) = bar {
+        output = match output {
+            OutputFormat::Template(t) => OutputFormat::Bar(bar, Some(t)),
+            _ => OutputFormat::Bar(bar, None),
+        };
+    }
+    args.no_tui |= output != OutputFormat::Text;
+    // "{max.3}" needs four days of forecast
+    if let OutputFormat::Template(t) | OutputFormat::Bar(_, Some(t)) = &output {
+        args.forecast = args.forecast.max(t.days_needed() as i32);
+    }
+    // bars and prompts poll often, don't spend the quota on them
+    let status_line = matches!(output, OutputFormat::Template(_) | OutputFormat::Bar(..))
+        || matches!(args.command, Command::Export | Command::Ics);
+    let max_age = max_age.unwrap_or(if status_line { DEF_STATUS_MAX_AGE } else { 0 });
+    args.max_age = Duration::from_secs(max_age.saturating_mul(60));
+    args.output = output;
+    args.hourly = hourly;
+    args.openmetrics = openmetrics;
+    args.report = report;
+    args.out_path = out_path;
+    args.serve = serve;
+    args.profile = profile;
+    args.places = places;
+    args.typed_location = typed_location;
+    Ok(args)
+}
+
+/// builds the command from its positional arguments
+fn command_from(
+    spec: &'static CmdSpec,
+    positionals: Vec<String>,
+    places_cmd: Option<PlacesCmd>,
+) -> Result<Command, ArgError> {
+    let unexpected = |arg: &str| ArgError::ExtraArg {
+        arg: arg.to_string(),
+        command: spec.name,
+    };
+    let command = match spec.name {
+        "search" => {
+            // no need to quote names with spaces here
+            let query = positionals.join(" ");
+            if query.trim().is_empty() {
+                return Err(ArgError::Usage(
+                    "Usage: regn search <str>".to_string(),
+                ));
+            }
+            return Ok(Command::Search(query));
         }
+        "config" => match positionals.first().map(String::as_str) {
+            None | Some("show") => Command::Config(ConfigCmd::Show),
+            Some("path") => Command::Config(ConfigCmd::Path),
+            Some(other) => return Err(unexpected(other)),
+        },
+        "places" => {
+            return Ok(Command::Places(places_cmd.unwrap_or(PlacesCmd::List)));
+        }
+        "completions" => match positionals.first().map(String::as_str) {
+            Some("__locations") => Command::CompleteLocations,
+            Some(sh) => match Shell::parse(sh) {
+                Some(shell) => Command::Completions(shell),
+                None => {
+                    return Err(invalid("shell", sh, "bash, zsh or fish"));
+                }
+            },
+            None => {
+                return Err(ArgError::Usage(
+                    "Usage: regn completions <bash|zsh|fish>".to_string(),
+                ));
+            }
+        },
+        "init" => Command::Init,
+        "export" => Command::Export,
+        "report" => Command::Report,
+        "ics" => Command::Ics,
+        "man" => Command::Man,
+        "now" => Command::Now,
+        "forecast" => Command::Forecast,
+        "hourly" => Command::Hourly,
+        _ => Command::Tui,
+    };
+    let max = usize::from(matches!(
+        command,
+        Command::Config(_) | Command::Completions(_) | Command::CompleteLocations
+    ));
+    match positionals.get(max) {
+        Some(arg) => Err(unexpected(arg)),
+        None => Ok(command),
     }
+}
 
-    if forecast > MAX_FORECAST {
-        forecast = MAX_FORECAST
+/// turns the merged settings into arguments, filling in defaults
+fn resolve_settings(
+    s: Settings,
+    mut keys: HashMap<String, KeySource>,
+    no_tui: bool,
+    help: bool,
+) -> Result<Arguments, ArgError> {
+    let unit_system = match s.units {
+        Some(v) => parse_value(&v, "units", "metric, imperial or uk", UnitSystem::parse)?,
+        None => UnitSystem::Metric,
+    };
+    // per-quantity overrides are applied on top of the unit system
+    let mut units = Units::from_system(unit_system);
+    if let Some(v) = s.temp {
+        units.temp = parse_value(&v, "temp", "c or f", TempUnit::parse)?;
+    }
+    if let Some(v) = s.wind {
+        units.wind = parse_value(&v, "wind", "kmh, ms, mph or kn", WindUnit::parse)?;
+    }
+    if let Some(v) = s.precip {
+        units.precip = parse_value(&v, "precip", "mm or in", PrecipUnit::parse)?;
+    }
+    if let Some(v) = s.pressure {
+        units.pressure =
+            parse_value(&v, "pressure", "hpa or inhg", PressureUnit::parse)?;
     }
 
-    Arguments {
+    if let Some(f) = &s.date_format
+        && !is_valid_date_format(f)
+    {
+        return Err(invalid("date-format", f, "a strftime date format"));
+    }
+    if let Some(f) = &s.time_format
+        && !is_valid_time_format(f)
+    {
+        return Err(invalid("time-format", f, "a strftime time format"));
+    }
+    let clock = match s.clock {
+        Some(v) => parse_value(&v, "clock", "12 or 24", Clock::parse)?,
+        None => Clock::H24,
+    };
+
+    let time_zone = match s.tz {
+        Some(v) => parse_value(
+            &v,
+            "tz",
+            "location, local, utc or an IANA name",
+            DisplayZone::parse,
+        )?,
+        None => DisplayZone::Location,
+    };
+    let provider = match s.provider {
+        Some(v) => parse_value(&v, "provider", "weatherapi", Provider::parse)?,
+        None => Provider::WeatherApi,
+    };
+    let theme = match s.theme {
+        Some(v) => parse_value(&v, "theme", "default, mono or ocean", Theme::parse)?,
+        None => Theme::Default,
+    };
+    let color = match s.color {
+        Some(v) => parse_value(&v, "color", "auto, always or never", ColorChoice::parse)?,
+        None => ColorChoice::Auto,
+    };
+
+    // a provider's own key source wins over the shared key_command
+    let mut key_source = keys.remove(provider.name()).unwrap_or_default();
+    if key_source.key_command.is_none() && key_source.key_file.is_none() {
+        key_source.key_command = s.key_command;
+    }
+
+    let forecast = s.forecast_days.unwrap_or(DEF_FORECAST);
+    if !(MIN_FORECAST..=MAX_FORECAST).contains(&forecast) {
+        return Err(days_out_of_range("forecast_days", &forecast.to_string()));
+    }
+
+    let fps = s.fps.unwrap_or(DEF_FPS);
+    if !(MIN_FPS..=MAX_FPS).contains(&fps) {
+        return Err(invalid("fps", &format!("{fps:?}"), &format!("a number from {MIN_FPS} to {MAX_FPS}")));
+    }
+
+    Ok(Arguments {
+        command: Command::Tui,
         no_tui,
-        location,
+        location: s.location.unwrap_or(DEF_LOCATION.to_string()),
         help,
+        help_topic: None,
+        version: false,
         forecast,
+        hours: DEF_HOURS,
+        units,
+        time_format: TimeFormat::new(s.date_format, s.time_format, clock),
+        time_zone,
+        provider,
+        theme,
+        color,
+        fps,
+        animations: s.animations.unwrap_or(true),
+        charts: s.charts.unwrap_or(false),
+        graph: s.graph.unwrap_or(false),
+        key_source,
+        output: OutputFormat::Text,
+        max_age: Duration::ZERO,
+        hourly: false,
+        openmetrics: false,
+        report: None,
+        out_path: None,
+        serve: None,
+        profile: None,
+        places: BTreeMap::new(),
+        typed_location: None,
+    })
+}
+
+fn parse_days(v: &str, flag: &str) -> Result<i32, ArgError> {
+    match v.parse::<i32>() {
+        Ok(d) if (MIN_FORECAST..=MAX_FORECAST).contains(&d) => Ok(d),
+        _ => Err(days_out_of_range(flag, v)),
+    }
+}
+
+fn parse_hours(v: &str, flag: &str) -> Result<i32, ArgError> {
+    let max = MAX_FORECAST * 24;
+    match v.parse::<i32>() {
+        Ok(h) if (1..=max).contains(&h) => Ok(h),
+        _ => Err(invalid(flag, v, &format!("a whole number from 1 to {max}"))),
+    }
+}
+
+fn days_out_of_range(name: &str, v: &str) -> ArgError {
+    invalid(
+        name,
+        v,
+        &format!("a whole number from {MIN_FORECAST} to {MAX_FORECAST}"),
+    )
+}
+
+fn invalid(name: &str, value: &str, expected: &str) -> ArgError {
+    ArgError::InvalidValue {
+        name: name.to_string(),
+        value: value.to_string(),
+        expected: expected.to_string(),
+    }
+}
+
+/// helper: parse a setting, naming it in the error
+fn parse_value<T>(
+    v: &str,
+    name: &str,
+    expected: &str,
+    parse: fn(&str) -> Option<T>,
+) -> Result<T, ArgError> {
+    parse(v).ok_or(invalid(name, v, expected))
+}
+
+/// "-l/--location"
+fn display_flag(spec: &OptSpec) -> String {
+    match spec.short {
+        Some(c) => format!("-{c}/--{}", spec.long),
+        None => format!("--{}", spec.long),
+    }
+}
+
+fn is_known_flag(arg: &str) -> bool {
+    match arg.strip_prefix("--") {
+        Some(long) => {
+            let name = long.split_once('=').map_or(long, |(n, _)| n);
+            OPTIONS.iter().any(|o| o.long == name)
+        }
+        None => {
+            let short = arg.strip_prefix('-').and_then(|s| s.chars().next());
+            short.is_some_and(|c| OPTIONS.iter().any(|o| o.short == Some(c)))
+        }
+    }
+}
+
+/// the closest candidate within a couple of typos, if any
+fn suggest<'a>(
+    input: &str,
+    candidates: impl Iterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let max = (input.chars().count() / 3).clamp(1, 3);
+    candidates
+        .map(|c| (edit_distance(input, c), c))
+        .filter(|(d, _)| *d <= max)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, c)| c)
+}
+
+/// edit distance where swapping two neighbouring characters counts as
+/// one typo ("hlep" -> "help")
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            d[i][j] = (d[i - 1][j - 1] + cost)
+                .min(d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<Arguments, ArgError> {
+        parse_args(s.split_whitespace().map(str::to_string))
+    }
+
+    #[test]
+    fn distances() {
+        assert_eq!(edit_distance("help", "help"), 0);
+        assert_eq!(edit_distance("hlep", "help"), 1);
+        assert_eq!(edit_distance("forcast", "forecast"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "now"), 3);
+    }
+
+    #[test]
+    fn suggestions() {
+        let names = || COMMANDS.iter().map(|c| c.name);
+        assert_eq!(suggest("forcast", names()), Some("forecast"));
+        assert_eq!(suggest("hlep", names()), Some("help"));
+        assert_eq!(suggest("paris", names()), None);
+    }
+
+    #[test]
+    fn usage_errors() {
+        assert_eq!(
+            parse("--locaton x").err(),
+            Some(ArgError::UnknownFlag {
+                flag: "--locaton".to_string(),
+                suggestion: Some("--location".to_string()),
+            })
+        );
+        assert_eq!(
+            parse("forcast").err(),
+            Some(ArgError::UnexpectedArg {
+                arg: "forcast".to_string(),
+                suggestion: Some("forecast".to_string()),
+            })
+        );
+        assert_eq!(
+            parse("--location").err(),
+            Some(ArgError::MissingValue { flag: "-l/--location".to_string() })
+        );
+        assert_eq!(
+            parse("--text=yes").err(),
+            Some(ArgError::UnexpectedValue { flag: "-t/--text".to_string() })
+        );
+        assert!(matches!(parse("--format {nope}"), Err(ArgError::Usage(_))));
+        for usage in ["search", "report", "completions", "places frobnicate", "--bogus"] {
+            assert_eq!(parse(usage).err().map(|e| e.exit_code()), Some(2), "{usage}");
+        }
+    }
+
+    #[test]
+    fn fps_range() {
+        let fps = |v| {
+            let s = Settings { fps: Some(v), ..Settings::default() };
+            resolve_settings(s, HashMap::new(), false, false).map(|a| a.fps)
+        };
+        assert_eq!(fps(1.0), Ok(1.0));
+        assert_eq!(fps(240.0), Ok(240.0));
+        for bad in [1e-300, 0.0, -5.0, 241.0, f64::NAN, f64::INFINITY] {
+            assert!(fps(bad).is_err(), "{bad}");
+        }
     }
 }
@@ -0,0 +1,87 @@
+use std::io;
+
+use serde::Serialize;
+
+use crate::{
+    Regn,
+    datetime::DisplayZone,
+    template::Template,
+    theme::{condition_rgb, hex},
+};
+
+/// status bars --bar can write for
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Bar {
+    Waybar,
+    I3blocks,
+    Polybar,
+}
+
+impl Bar {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "waybar" => Some(Self::Waybar),
+            "i3blocks" => Some(Self::I3blocks),
+            "polybar" => Some(Self::Polybar),
+            _ => None,
+        }
+    }
+}
+
+/// what waybar's custom module reads with "return-type": "json"
+#[derive(Serialize)]
+struct WaybarOutput {
+    text: String,
+    tooltip: String,
+    class: &'static str,
+    percentage: i32,
+}
+
+/// the text when no --format is given
+const DEF_BAR_TEXT: &str = "{icon} {temp:.0}°";
+/// i3blocks shows the short text when the bar runs out of space
+const SHORT_BAR_TEXT: &str = "{temp:.0}°";
+
+impl Regn {
+    /// `--bar`, with --format as the text if given
+    pub fn print_bar(&self, bar: Bar, text: Option<&Template>) -> io::Result<()> {
+        let default = Template::parse(DEF_BAR_TEXT).map_err(io::Error::other)?;
+        let text = self.render_template(text.unwrap_or(&default));
+        let color = hex(condition_rgb(self.weather.current_condition));
+
+        match bar {
+            Bar::Waybar => {
+                let (updated, _) = DisplayZone::Local.wall_clock(self.weather.fetched_at, &self.weather.zone);
+                let tooltip = format!(
+                    "{}\n\nUpdated {}",
+                    self.format_weather_data().join("\n").trim(),
+                    self.args.time_format.time(updated.time())
+                );
+                let out = WaybarOutput {
+                    text: pango_escape(&text),
+                    tooltip: pango_escape(&tooltip),
+                    class: self.weather.current_condition.name(),
+                    percentage: self
+                        .weather
+                        .forecast_days
+                        .first()
+                        .map_or(0, |d| d.day.daily_chance_of_rain),
+                };
+                println!("{}", serde_json::to_string(&out).map_err(io::Error::other)?);
+            }
+            Bar::I3blocks => {
+                let short = Template::parse(SHORT_BAR_TEXT).map_err(io::Error::other)?;
+                println!("{text}");
+                println!("{}", self.render_template(&short));
+                println!("{color}");
+            }
+            Bar::Polybar => println!("%{{F{color}}}{text}%{{F-}}"),
+        }
+        Ok(())
+    }
+}
+
+/// waybar reads text and tooltip as pango markup
+fn pango_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
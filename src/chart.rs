@@ -0,0 +1,198 @@
+use chrono::DateTime;
+
+use crate::{
+    Regn,
+    api::{api_main::CurrentCondition, request::RespDay},
+    theme::{condition_rgb, temp_rgb},
+};
+
+/// eighths of a cell, from empty to full
+const BLOCKS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// rows used for the chance of rain bars
+const BAR_HEIGHT: usize = 4;
+/// columns per day in the daily band
+const DAY_WIDTH: usize = 3;
+/// width of the labels in front of every chart line
+const LABEL_WIDTH: usize = 11;
+
+/// the block for `v` on a scale from `lo` to `hi`. never empty, so the
+/// lowest value still shows.
+pub fn spark(v: f64, lo: f64, hi: f64) -> char {
+    let t = match hi - lo {
+        span if span > 0.0 => ((v - lo) / span).clamp(0.0, 1.0),
+        _ => 0.5,
+    };
+    BLOCKS[1 + (t * 7.0).round() as usize]
+}
+
+/// vertical bars for values from 0 to `max`, `height` rows tall.
+/// returns the cells row by row, top row first.
+pub fn bars(values: &[f64], max: f64, height: usize) -> Vec<Vec<char>> {
+    let eighths: Vec<usize> = values
+        .iter()
+        .map(|v| match max > 0.0 {
+            true => ((v / max).clamp(0.0, 1.0) * (height * 8) as f64).round() as usize,
+            false => 0,
+        })
+        .collect();
+    (0..height)
+        .map(|row| {
+            let floor = (height - 1 - row) * 8;
+            eighths
+                .iter()
+                .map(|e| BLOCKS[e.saturating_sub(floor).min(8)])
+                .collect()
+        })
+        .collect()
+}
+
+/// labels placed at their columns, dropping any that would run into
+/// the one before
+pub fn axis(labels: &[(usize, String)]) -> String {
+    let mut s = String::new();
+    for (col, label) in labels {
+        let len = s.chars().count();
+        if *col < len + usize::from(len > 0) {
+            continue;
+        }
+        s.push_str(&" ".repeat(col - len));
+        s.push_str(label);
+    }
+    s
+}
+
+impl Regn {
+    /// the daily band, the hourly sparkline and the chance of rain,
+    /// lined up under each other
+    pub fn format_charts(&self) -> Vec<String> {
+        let mut s = vec![String::new()];
+        s.extend(self.format_daily_band());
+        s.push(String::new());
+        s.extend(self.format_hourly_chart());
+        s
+    }
+
+    /// max and min for every forecast day, on a shared scale so the two
+    /// lines read as a band
+    fn format_daily_band(&self) -> Vec<String> {
+        let u = self.args.units;
+        let days = &self.weather.forecast_days;
+        if days.is_empty() {
+            return Vec::new();
+        }
+        let lo = days.iter().map(|d| d.day.mintemp_c).fold(f64::INFINITY, f64::min);
+        let hi = days.iter().map(|d| d.day.maxtemp_c).fold(f64::NEG_INFINITY, f64::max);
+
+        let line = |pick: fn(&RespDay) -> f64| -> String {
+            days.iter()
+                .map(|d| {
+                    let v = pick(&d.day);
+                    let cell = spark(v, lo, hi).to_string().repeat(DAY_WIDTH - 1);
+                    format!("{} ", self.paint(cell, temp_rgb(v)))
+                })
+                .collect()
+        };
+        let days_axis: String = days
+            .iter()
+            .map(|d| {
+                let name: String = d.date.format("%a").to_string().chars().take(DAY_WIDTH - 1).collect();
+                format!("{name:<DAY_WIDTH$}")
+            })
+            .collect();
+
+        vec![
+            format!("{:<LABEL_WIDTH$}{}{}", "Daily max", line(|d| d.maxtemp_c), u.temp(hi)),
+            format!("{:<LABEL_WIDTH$}{}{}", "Daily min", line(|d| d.mintemp_c), u.temp(lo)),
+            format!("{:<LABEL_WIDTH$}{}", "", days_axis.trim_end()),
+        ]
+    }
+
+    /// temperature as a sparkline and the chance of rain as bars, one
+    /// column per upcoming hour
+    fn format_hourly_chart(&self) -> Vec<String> {
+        let u = self.args.units;
+        let tf = &self.args.time_format;
+        let hours: Vec<_> = self.upcoming_hours().collect();
+        if hours.is_empty() {
+            return Vec::new();
+        }
+        let lo = hours.iter().map(|h| h.temp_c).fold(f64::INFINITY, f64::min);
+        let hi = hours.iter().map(|h| h.temp_c).fold(f64::NEG_INFINITY, f64::max);
+
+        let mut s = Vec::new();
+        let temps: String = hours
+            .iter()
+            .map(|h| self.paint(spark(h.temp_c, lo, hi).to_string(), temp_rgb(h.temp_c)))
+            .collect();
+        s.push(format!(
+            "{:<LABEL_WIDTH$}{temps} {} to {}",
+            format!("Next {}h", hours.len()),
+            u.temp(lo),
+            u.temp(hi)
+        ));
+
+        let rain: Vec<f64> = hours.iter().map(|h| h.chance_of_rain as f64).collect();
+        let rain_rgb = condition_rgb(CurrentCondition::Rain);
+        for (i, row) in bars(&rain, 100.0, BAR_HEIGHT).into_iter().enumerate() {
+            let label = match i {
+                0 => "Rain 100%",
+                _ if i == BAR_HEIGHT - 1 => "       0%",
+                _ => "",
+            };
+            let cells: String = row.into_iter().collect();
+            s.push(format!("{label:<LABEL_WIDTH$}{}", self.paint(cells, rain_rgb)));
+        }
+
+        // a time label every six hours
+        let labels: Vec<(usize, String)> = hours
+            .iter()
+            .enumerate()
+            .filter_map(|(i, h)| {
+                let t = DateTime::from_timestamp(h.time_epoch, 0)?;
+                let (wall, _) = self.args.time_zone.wall_clock(t, &self.weather.zone);
+                (i % 6 == 0).then(|| (i, tf.time(wall.time())))
+            })
+            .collect();
+        s.push(format!("{:<LABEL_WIDTH$}{}", "", axis(&labels)));
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sparks() {
+        assert_eq!(spark(0.0, 0.0, 10.0), '▁');
+        assert_eq!(spark(10.0, 0.0, 10.0), '█');
+        assert_eq!(spark(20.0, 0.0, 10.0), '█');
+        assert_eq!(spark(-5.0, 0.0, 10.0), '▁');
+        // no span, so everything is in the middle
+        assert_eq!(spark(5.0, 5.0, 5.0), '▅');
+    }
+
+    #[test]
+    fn bar_rows() {
+        assert_eq!(
+            bars(&[0.0, 5.0, 10.0], 10.0, 2),
+            vec![vec![' ', ' ', '█'], vec![' ', '█', '█']]
+        );
+        assert_eq!(bars(&[1.0, 2.0], 0.0, 1), vec![vec![' ', ' ']]);
+        assert!(bars(&[1.0], 1.0, 0).is_empty());
+    }
+
+    #[test]
+    fn axis_labels() {
+        let labels = |l: &[(usize, &str)]| -> Vec<(usize, String)> {
+            l.iter().map(|(c, s)| (*c, s.to_string())).collect()
+        };
+        assert_eq!(axis(&labels(&[(2, "00")])), "  00");
+        // "12" would touch "06", so it is left out
+        assert_eq!(
+            axis(&labels(&[(0, "00"), (3, "06"), (4, "12"), (8, "18")])),
+            "00 06   18"
+        );
+        assert_eq!(axis(&[]), "");
+    }
+}
@@ -0,0 +1,62 @@
+use crate::{
+    api::api_main::search_main,
+    arg::{Arguments, ConfigCmd},
+    config::config_path,
+};
+
+/// `regn search <str>`
+pub fn run_search(args: &Arguments, query: &str) -> Result<(), String> {
+    let results = search_main(&args.provider, &args.key_source, query)?;
+    if results.is_empty() {
+        println!("No locations matching \"{query}\" were found.");
+        return Ok(());
+    }
+    for r in results.iter() {
+        let place = [r.name.as_str(), r.region.as_str(), r.country.as_str()]
+            .into_iter()
+            .filter(|p| !p.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!("{place} : -l \"{},{}\"", r.lat, r.lon);
+    }
+    Ok(())
+}
+
+/// `regn config [show|path]`
+pub fn run_config(args: &Arguments, cmd: ConfigCmd) -> Result<(), String> {
+    match cmd {
+        ConfigCmd::Path => println!("{}", config_path().display()),
+        ConfigCmd::Show => {
+            // the settings in effect, with flags and profile applied
+            println!("# {}", config_path().display());
+            if let Some(p) = &args.profile {
+                println!("# profile: {p}");
+            }
+            let u = &args.units;
+            println!("location = {:?}", args.location);
+            println!("forecast_days = {}", args.forecast);
+            println!("temp = {:?}", u.temp.name());
+            println!("wind = {:?}", u.wind.name());
+            println!("precip = {:?}", u.precip.name());
+            println!("pressure = {:?}", u.pressure.name());
+            println!("date_format = {:?}", args.time_format.date);
+            println!("time_format = {:?}", args.time_format.time);
+            println!("tz = {:?}", args.time_zone.name());
+            println!("provider = {:?}", args.provider.name());
+            println!("theme = {:?}", args.theme.name());
+            println!("color = {:?}", args.color.name());
+            println!("fps = {:?}", args.fps);
+            println!("animations = {}", args.animations);
+            println!("charts = {}", args.charts);
+            println!("graph = {}", args.graph);
+            println!("max_age = {}", args.max_age.as_secs() / 60);
+            if !args.places.is_empty() {
+                println!("\n[places]");
+                for (name, loc) in args.places.iter() {
+                    println!("{name} = {loc:?}");
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,307 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    arg::{COMMANDS, CmdSpec, OPTIONS, OptSpec},
+    help::APP_NAME,
+    history::load_history,
+};
+
+/// shells `regn completions` can write a script for
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "bash" => Some(Self::Bash),
+            "zsh" => Some(Self::Zsh),
+            "fish" => Some(Self::Fish),
+            _ => None,
+        }
+    }
+}
+
+/// `regn completions <shell>`. the scripts are generated from the
+/// definitions in arg.rs, like the help text.
+pub fn print_completions(shell: Shell) -> Result<(), String> {
+    let script = match shell {
+        Shell::Bash => bash_script(),
+        Shell::Zsh => zsh_script(),
+        Shell::Fish => fish_script(),
+    };
+    print!("{script}");
+    Ok(())
+}
+
+/// saved places first, then recently used locations
+pub fn print_locations(places: &BTreeMap<String, String>) -> Result<(), String> {
+    for name in places.keys() {
+        println!("@{name}");
+    }
+    for loc in load_history() {
+        println!("{loc}");
+    }
+    Ok(())
+}
+
+/// the words a command takes as arguments, from its usage string:
+/// "[show|path]" gives show and path, "<str>" gives nothing
+fn arg_words(cmd: &CmdSpec) -> Vec<&'static str> {
+    if cmd.name == "help" {
+        return COMMANDS.iter().map(|c| c.name).collect();
+    }
+    let args = cmd.args;
+    if !args.contains('|') {
+        return Vec::new();
+    }
+    args[1..args.len() - 1]
+        .split('|')
+        .filter_map(|alt| alt.split_whitespace().next())
+        .filter(|w| !w.starts_with('<'))
+        .collect()
+}
+
+/// the help text up to the first parenthesis, short enough for a menu
+fn short_help(opt: &OptSpec) -> &'static str {
+    opt.help.split(" (").next().unwrap_or(opt.help).trim()
+}
+
+/// options are completed as given to the default command when no
+/// command has been typed
+fn default_cmd() -> &'static CmdSpec {
+    CmdSpec::find("tui").unwrap_or(&COMMANDS[0])
+}
+
+fn flags(opt: &OptSpec) -> Vec<String> {
+    let mut f: Vec<String> = opt.short.map(|c| format!("-{c}")).into_iter().collect();
+    f.push(format!("--{}", opt.long));
+    f
+}
+
+fn bash_script() -> String {
+    let mut s = String::new();
+    s.push_str(&format!("# bash completion for {APP_NAME}\n"));
+    s.push_str(&format!("_{APP_NAME}() {{\n"));
+    s.push_str("    local cur prev cmd i words\n");
+    s.push_str("    cur=\"${COMP_WORDS[COMP_CWORD]}\"\n");
+    s.push_str("    prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n\n");
+
+    // the command is the first word that isn't a flag or a flag's value
+    let value_flags: Vec<String> = OPTIONS
+        .iter()
+        .filter(|o| o.value.is_some())
+        .flat_map(flags)
+        .collect();
+    let names: Vec<&str> = COMMANDS.iter().map(|c| c.name).collect();
+    s.push_str("    cmd=\"\"\n");
+    s.push_str("    for ((i = 1; i < COMP_CWORD; i++)); do\n");
+    s.push_str("        case \"${COMP_WORDS[i-1]}\" in\n");
+    s.push_str(&format!("            {}) continue ;;\n", value_flags.join("|")));
+    s.push_str("        esac\n");
+    s.push_str("        case \"${COMP_WORDS[i]}\" in\n");
+    s.push_str(&format!(
+        "            {}) cmd=\"${{COMP_WORDS[i]}}\"; break ;;\n",
+        names.join("|")
+    ));
+    s.push_str("        esac\n");
+    s.push_str("    done\n\n");
+
+    s.push_str("    case \"$prev\" in\n");
+    for opt in OPTIONS.iter().filter(|o| o.value.is_some()) {
+        let pattern = flags(opt).join("|");
+        if opt.long == "location" {
+            s.push_str(&format!("        {pattern})\n"));
+            s.push_str("            local IFS=$'\\n'\n");
+            s.push_str(&format!(
+                "            COMPREPLY=($(compgen -W \"$({APP_NAME} completions __locations 2>/dev/null)\" -- \"$cur\"))\n"
+            ));
+            s.push_str("            COMPREPLY=(\"${COMPREPLY[@]// /\\\\ }\")\n");
+            s.push_str("            return ;;\n");
+        } else if !opt.choices.is_empty() {
+            s.push_str(&format!(
+                "        {pattern}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")); return ;;\n",
+                opt.choices.join(" ")
+            ));
+        } else {
+            s.push_str(&format!("        {pattern}) return ;;\n"));
+        }
+    }
+    s.push_str("    esac\n\n");
+
+    s.push_str("    case \"$cmd\" in\n");
+    let default_opts: Vec<String> = default_cmd().option_specs().flat_map(flags).collect();
+    s.push_str(&format!(
+        "        \"\") words=\"{} {}\" ;;\n",
+        names.join(" "),
+        default_opts.join(" ")
+    ));
+    for cmd in COMMANDS {
+        let mut words: Vec<String> = arg_words(cmd).iter().map(|w| w.to_string()).collect();
+        words.extend(cmd.option_specs().flat_map(flags));
+        s.push_str(&format!("        {}) words=\"{}\" ;;\n", cmd.name, words.join(" ")));
+    }
+    s.push_str("    esac\n");
+    s.push_str("    COMPREPLY=($(compgen -W \"$words\" -- \"$cur\"))\n");
+    s.push_str("}\n");
+    s.push_str(&format!("complete -F _{APP_NAME} {APP_NAME}\n"));
+    s
+}
+
+/// escapes text for use inside a single quoted zsh spec
+fn zsh_escape(s: &str) -> String {
+    s.replace('\'', "'\\''")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+        .replace(':', "\\:")
+}
+
+/// one _arguments spec, e.g. '(-l --location)'{-l,--location}'[...]:str:_regn_locations'
+fn zsh_option(opt: &OptSpec) -> String {
+    let desc = zsh_escape(short_help(opt));
+    let action = match opt.value {
+        None => String::new(),
+        Some(v) if opt.long == "location" => format!(":{v}:_{APP_NAME}_locations"),
+        Some(v) if !opt.choices.is_empty() => format!(":{v}:({})", opt.choices.join(" ")),
+        Some(v) => format!(":{v}: "),
+    };
+    match opt.short {
+        Some(c) => format!(
+            "'(-{c} --{l})'{{-{c},--{l}}}'[{desc}]{action}'",
+            l = opt.long
+        ),
+        None => format!("'--{}[{desc}]{action}'", opt.long),
+    }
+}
+
+fn zsh_script() -> String {
+    let mut s = String::new();
+    s.push_str(&format!("#compdef {APP_NAME}\n\n"));
+
+    s.push_str(&format!("_{APP_NAME}_locations() {{\n"));
+    s.push_str("    local -a locs\n");
+    s.push_str(&format!(
+        "    locs=(\"${{(@f)$({APP_NAME} completions __locations 2>/dev/null)}}\")\n"
+    ));
+    s.push_str("    compadd -a locs\n");
+    s.push_str("}\n\n");
+
+    s.push_str(&format!("_{APP_NAME}() {{\n"));
+    s.push_str("    local curcontext=\"$curcontext\" state line\n");
+    s.push_str("    local -a commands\n");
+    s.push_str("    commands=(\n");
+    for cmd in COMMANDS {
+        s.push_str(&format!("        '{}:{}'\n", cmd.name, zsh_escape(cmd.about)));
+    }
+    s.push_str("    )\n\n");
+
+    s.push_str("    _arguments -C -s \\\n");
+    for opt in default_cmd().option_specs() {
+        s.push_str(&format!("        {} \\\n", zsh_option(opt)));
+    }
+    s.push_str("        '1: :->cmd' \\\n");
+    s.push_str("        '*:: :->args'\n\n");
+
+    s.push_str("    case $state in\n");
+    s.push_str("        cmd) _describe -t commands 'command' commands ;;\n");
+    s.push_str("        args)\n");
+    s.push_str("            case $line[1] in\n");
+    for cmd in COMMANDS {
+        s.push_str(&format!("                {})\n", cmd.name));
+        s.push_str("                    _arguments -s \\\n");
+        for opt in cmd.option_specs() {
+            s.push_str(&format!("                        {} \\\n", zsh_option(opt)));
+        }
+        let words = arg_words(cmd);
+        if words.is_empty() {
+            s.push_str("                        '*: :' ;;\n");
+        } else {
+            s.push_str(&format!(
+                "                        '1: :({})' '*: :' ;;\n",
+                words.join(" ")
+            ));
+        }
+    }
+    s.push_str("            esac ;;\n");
+    s.push_str("    esac\n");
+    s.push_str("}\n\n");
+    s.push_str(&format!("_{APP_NAME} \"$@\"\n"));
+    s
+}
+
+fn fish_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+fn fish_script() -> String {
+    let mut s = String::new();
+    let names: Vec<&str> = COMMANDS.iter().map(|c| c.name).collect();
+    let all = names.join(" ");
+    s.push_str(&format!("# fish completion for {APP_NAME}\n"));
+    s.push_str(&format!("complete -c {APP_NAME} -f\n\n"));
+
+    for cmd in COMMANDS {
+        s.push_str(&format!(
+            "complete -c {APP_NAME} -n 'not __fish_seen_subcommand_from {all}' -a {} -d '{}'\n",
+            cmd.name,
+            fish_escape(cmd.about)
+        ));
+    }
+    s.push('\n');
+    for cmd in COMMANDS {
+        let words = arg_words(cmd);
+        if !words.is_empty() {
+            s.push_str(&format!(
+                "complete -c {APP_NAME} -n '__fish_seen_subcommand_from {}' -a '{}'\n",
+                cmd.name,
+                words.join(" ")
+            ));
+        }
+    }
+    s.push('\n');
+
+    for opt in OPTIONS {
+        let accepting: Vec<&str> = COMMANDS
+            .iter()
+            .filter(|c| c.accepts(opt.long))
+            .map(|c| c.name)
+            .collect();
+        // offered before any command when the default command takes it
+        let condition = if default_cmd().accepts(opt.long) {
+            let rejecting: Vec<&str> = names
+                .iter()
+                .copied()
+                .filter(|n| !accepting.contains(n))
+                .collect();
+            match rejecting.is_empty() {
+                true => String::new(),
+                false => format!(
+                    " -n 'not __fish_seen_subcommand_from {}'",
+                    rejecting.join(" ")
+                ),
+            }
+        } else {
+            format!(" -n '__fish_seen_subcommand_from {}'", accepting.join(" "))
+        };
+
+        let mut line = format!("complete -c {APP_NAME}{condition}");
+        if let Some(c) = opt.short {
+            line.push_str(&format!(" -s {c}"));
+        }
+        line.push_str(&format!(" -l {}", opt.long));
+        if opt.value.is_some() {
+            line.push_str(" -r");
+        }
+        if opt.long == "location" {
+            line.push_str(&format!(" -a '({APP_NAME} completions __locations 2>/dev/null)'"));
+        } else if !opt.choices.is_empty() {
+            line.push_str(&format!(" -a '{}'", opt.choices.join(" ")));
+        }
+        line.push_str(&format!(" -d '{}'\n", fish_escape(short_help(opt))));
+        s.push_str(&line);
+    }
+    s
+}
@@ -0,0 +1,210 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
+
+use home::home_dir;
+use serde::Deserialize;
+use toml_edit::DocumentMut;
+
+use crate::api::key::KeySource;
+
+/// every setting that can be given in the config file, in a profile or
+/// on the command line. unset values fall through to the next layer.
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Settings {
+    pub location: Option<String>,
+    pub units: Option<String>,
+    pub temp: Option<String>,
+    pub wind: Option<String>,
+    pub precip: Option<String>,
+    pub pressure: Option<String>,
+    pub forecast_days: Option<i32>,
+    pub provider: Option<String>,
+    pub theme: Option<String>,
+    pub color: Option<String>,
+    pub fps: Option<f64>,
+    pub animations: Option<bool>,
+    pub charts: Option<bool>,
+    pub graph: Option<bool>,
+    pub date_format: Option<String>,
+    pub time_format: Option<String>,
+    pub clock: Option<String>,
+    pub tz: Option<String>,
+    /// minutes a cached response may be reused for
+    pub max_age: Option<u64>,
+    /// used for any provider without its own [keys.<provider>] entry
+    pub key_command: Option<String>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+pub struct Config {
+    /// name of the profile to use when --profile is not given
+    pub profile: Option<String>,
+    /// read from `rest` by load_config. serde ignores
+    /// deny_unknown_fields on a flattened struct, so typos at the top
+    /// level would go unnoticed.
+    #[serde(skip)]
+    pub settings: Settings,
+    #[serde(flatten)]
+    rest: toml::Table,
+    #[serde(default)]
+    pub profiles: HashMap<String, Settings>,
+    /// per-provider API key sources
+    #[serde(default)]
+    pub keys: HashMap<String, KeySource>,
+    /// named locations, used as `-l @name`
+    #[serde(default)]
+    pub places: BTreeMap<String, String>,
+}
+
+impl Settings {
+    /// values set in `top` take precedence over the ones in `self`
+    pub fn overlay(self, top: Settings) -> Settings {
+        Settings {
+            location: top.location.or(self.location),
+            units: top.units.or(self.units),
+            temp: top.temp.or(self.temp),
+            wind: top.wind.or(self.wind),
+            precip: top.precip.or(self.precip),
+            pressure: top.pressure.or(self.pressure),
+            forecast_days: top.forecast_days.or(self.forecast_days),
+            provider: top.provider.or(self.provider),
+            theme: top.theme.or(self.theme),
+            color: top.color.or(self.color),
+            fps: top.fps.or(self.fps),
+            animations: top.animations.or(self.animations),
+            charts: top.charts.or(self.charts),
+            graph: top.graph.or(self.graph),
+            date_format: top.date_format.or(self.date_format),
+            time_format: top.time_format.or(self.time_format),
+            clock: top.clock.or(self.clock),
+            tz: top.tz.or(self.tz),
+            max_age: top.max_age.or(self.max_age),
+            key_command: top.key_command.or(self.key_command),
+        }
+    }
+}
+
+impl Config {
+    /// the top-level settings with the chosen profile applied on top
+    pub fn resolve(mut self, profile: Option<&str>) -> Result<Settings, String> {
+        let name = match profile {
+            Some(p) => Some(p.to_string()),
+            None => self.profile.take(),
+        };
+        match name {
+            Some(name) => match self.profiles.remove(&name) {
+                Some(p) => Ok(self.settings.overlay(p)),
+                None => Err(format!(
+                    "The profile \"{name}\" does not exist in {}.",
+                    config_path().display()
+                )),
+            },
+            None => Ok(self.settings),
+        }
+    }
+}
+
+/// $XDG_CONFIG_HOME/regn, falling back to ~/.config/regn
+pub fn config_dir() -> PathBuf {
+    match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(d) if !d.is_empty() => PathBuf::from(d).join("regn"),
+        _ => home_dir().unwrap_or_default().join(".config").join("regn"),
+    }
+}
+
+/// $XDG_STATE_HOME/regn, falling back to ~/.local/state/regn
+pub fn state_dir() -> PathBuf {
+    match std::env::var_os("XDG_STATE_HOME") {
+        Some(d) if !d.is_empty() => PathBuf::from(d).join("regn"),
+        _ => home_dir()
+            .unwrap_or_default()
+            .join(".local")
+            .join("state")
+            .join("regn"),
+    }
+}
+
+/// $XDG_CACHE_HOME/regn, falling back to ~/.cache/regn
+pub fn cache_dir() -> PathBuf {
+    match std::env::var_os("XDG_CACHE_HOME") {
+        Some(d) if !d.is_empty() => PathBuf::from(d).join("regn"),
+        _ => home_dir().unwrap_or_default().join(".cache").join("regn"),
+    }
+}
+
+pub fn config_path() -> PathBuf {
+    config_dir().join("config.toml")
+}
+
+/// a missing config file is the same as an empty one
+pub fn load_config() -> Result<Config, String> {
+    let path = config_path();
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(Config::default());
+        }
+        Err(e) => {
+            return Err(format!("Could not read {}: {e}", path.display()));
+        }
+    };
+    parse_config(&contents).map_err(|e| format!("Invalid config file {}: {e}", path.display()))
+}
+
+fn parse_config(contents: &str) -> Result<Config, toml::de::Error> {
+    let mut config: Config = toml::from_str(contents)?;
+    config.settings = Settings::deserialize(toml::Value::Table(std::mem::take(&mut config.rest)))?;
+    Ok(config)
+}
+
+/// edits the config file in place, keeping comments and formatting.
+/// the file is created if it does not exist yet.
+pub fn edit_config(
+    f: impl FnOnce(&mut DocumentMut) -> Result<(), String>,
+) -> Result<(), String> {
+    let path = config_path();
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("Could not read {}: {e}", path.display())),
+    };
+    let mut doc = contents
+        .parse::<DocumentMut>()
+        .map_err(|e| format!("Invalid config file {}: {e}", path.display()))?;
+
+    f(&mut doc)?;
+
+    std::fs::create_dir_all(config_dir())
+        .and_then(|_| std::fs::write(&path, doc.to_string()))
+        .map_err(|e| format!("Could not write {}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn top_level_settings() {
+        let c = parse_config("location = \"Paris\"\nforecast_days = 4\n[places]\nhome = \"Oslo\"\n").unwrap();
+        assert_eq!(c.settings.location.as_deref(), Some("Paris"));
+        assert_eq!(c.settings.forecast_days, Some(4));
+        assert_eq!(c.places.get("home").map(String::as_str), Some("Oslo"));
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        assert!(parse_config("locaton = \"Paris\"\n").is_err());
+        assert!(parse_config("[profiles.x]\nforecast_dayz = 4\n").is_err());
+    }
+
+    #[test]
+    fn profile_overrides_top_level() {
+        let c = parse_config("location = \"Paris\"\ntemp = \"c\"\n[profiles.us]\ntemp = \"f\"\n").unwrap();
+        let s = c.resolve(Some("us")).unwrap();
+        assert_eq!(s.location.as_deref(), Some("Paris"));
+        assert_eq!(s.temp.as_deref(), Some("f"));
+    }
+}
@@ -3,15 +3,26 @@ use std::time::Duration;
 use crate::{ProgState, Regn};
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers, poll};
 
+/// the keys of the TUI, for `regn help`, the man page and the ? overlay
+pub const KEYS: &[(&str, &str)] = &[
+    ("[Esc]", "quit, or close the day view or prompt"),
+    ("[Ctrl-C]", "quit"),
+    ("[Tab] [Shift-Tab] [Left] [Right]", "move between panels"),
+    ("[Up] [Down] [j] [k]", "move over the forecast days"),
+    ("[Enter]", "open the selected day, or go back"),
+    ("[/]", "show another location, picked from saved places, history or a search"),
+    ("[g]", "toggle the temperature graph"),
+    ("[?]", "show these keys"),
+];
+
 impl Regn {
     pub fn controls(&mut self) -> std::io::Result<()> {
         if poll(Duration::ZERO)? {
             match self.prog_state {
-                ProgState::Main => {
-                    if let Event::Key(KeyEvent {
+                ProgState::Main => match event::read()? {
+                    Event::Key(KeyEvent {
                         code, modifiers, ..
-                    }) = event::read()?
-                    {
+                    }) => {
                         match (code, modifiers) {
                             // quit
                             (KeyCode::Esc, _) => {
@@ -24,10 +35,129 @@ impl Regn {
                             ) => {
                                 self.prog_state = ProgState::Quit;
                             }
+
+                            // move between panels
+                            (KeyCode::Tab | KeyCode::Right, _) => {
+                                self.move_focus(true)?;
+                            }
+                            (KeyCode::BackTab | KeyCode::Left, _) => {
+                                self.move_focus(false)?;
+                            }
+
+                            // pick a forecast day and open it
+                            (KeyCode::Down | KeyCode::Char('j'), _) => {
+                                self.move_day_cursor(true)?;
+                            }
+                            (KeyCode::Up | KeyCode::Char('k'), _) => {
+                                self.move_day_cursor(false)?;
+                            }
+                            (KeyCode::Enter, _) => {
+                                self.toggle_day_view()?;
+                            }
+
+                            // another location
+                            (KeyCode::Char('/'), _) => {
+                                self.open_prompt()?;
+                            }
+
+                            (KeyCode::Char('?'), _) => {
+                                self.toggle_help()?;
+                            }
+
+                            // temperature graph
+                            (KeyCode::Char('g'), _) => {
+                                self.show_graph = !self.show_graph;
+                                self.util_clear_screen()?;
+                            }
                             _ => {}
                         }
                     }
-                }
+                    Event::Resize(columns, rows) => {
+                        self.resize(columns, rows)?;
+                    }
+                    _ => {}
+                },
+
+                ProgState::Day => match event::read()? {
+                    Event::Key(KeyEvent {
+                        code, modifiers, ..
+                    }) => match (code, modifiers) {
+                        (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+                            self.prog_state = ProgState::Quit;
+                        }
+
+                        // back to the panels
+                        (KeyCode::Esc | KeyCode::Enter | KeyCode::Backspace, _) => {
+                            self.toggle_day_view()?;
+                        }
+
+                        (KeyCode::Down | KeyCode::Char('j'), _) => {
+                            self.move_day_cursor(true)?;
+                        }
+                        (KeyCode::Up | KeyCode::Char('k'), _) => {
+                            self.move_day_cursor(false)?;
+                        }
+                        _ => {}
+                    },
+                    Event::Resize(columns, rows) => {
+                        self.resize(columns, rows)?;
+                    }
+                    _ => {}
+                },
+
+                ProgState::Search => match event::read()? {
+                    Event::Key(KeyEvent {
+                        code, modifiers, ..
+                    }) => match (code, modifiers) {
+                        (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+                            self.prog_state = ProgState::Quit;
+                        }
+                        (KeyCode::Esc, _) => {
+                            self.close_prompt()?;
+                        }
+                        (KeyCode::Enter, _) => {
+                            self.prompt_submit()?;
+                        }
+                        (KeyCode::Tab, _) => {
+                            self.prompt_complete();
+                        }
+                        (KeyCode::Down, _) => {
+                            self.prompt_select(true);
+                        }
+                        (KeyCode::Up, _) => {
+                            self.prompt_select(false);
+                        }
+                        (KeyCode::Backspace, _) => {
+                            self.prompt_edit(None)?;
+                        }
+                        (KeyCode::Char(c), _) => {
+                            self.prompt_edit(Some(c))?;
+                        }
+                        _ => {}
+                    },
+                    Event::Resize(columns, rows) => {
+                        self.resize(columns, rows)?;
+                    }
+                    _ => {}
+                },
+
+                // any key closes the help
+                ProgState::Help => match event::read()? {
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('c'),
+                        modifiers: KeyModifiers::CONTROL,
+                        ..
+                    }) => {
+                        self.prog_state = ProgState::Quit;
+                    }
+                    Event::Key(_) => {
+                        self.toggle_help()?;
+                    }
+                    Event::Resize(columns, rows) => {
+                        self.resize(columns, rows)?;
+                    }
+                    _ => {}
+                },
 
                 ProgState::Quit => {}
             }
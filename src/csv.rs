@@ -0,0 +1,128 @@
+use std::io;
+
+use chrono::DateTime;
+
+use crate::{Regn, api::request::RespHour, arg::Command};
+
+impl Regn {
+    /// `--csv` and `--tsv`: one row per day, or per hour for `regn hourly`
+    /// and `--hourly`. numbers are in the chosen units, named in the header.
+    pub fn print_table(&self, sep: char) -> io::Result<()> {
+        let rows = match self.args.command == Command::Hourly || self.args.hourly {
+            true => self.hour_rows(),
+            false => self.day_rows(),
+        };
+        for row in rows {
+            let fields: Vec<String> = row.iter().map(|f| escape_field(f, sep)).collect();
+            println!("{}", fields.join(&sep.to_string()));
+        }
+        Ok(())
+    }
+
+    fn location_name(&self) -> String {
+        format!(
+            "{}, {}",
+            self.weather.location.name, self.weather.location.country
+        )
+    }
+
+    fn day_rows(&self) -> Vec<Vec<String>> {
+        let u = self.args.units;
+        let location = self.location_name();
+        let mut rows = vec![vec![
+            "date".to_string(),
+            "location".to_string(),
+            format!("min_{}", u.temp.name()),
+            format!("max_{}", u.temp.name()),
+            "condition".to_string(),
+            "chance_of_rain_pct".to_string(),
+            format!("max_wind_{}", u.wind.name()),
+        ]];
+        for d in self.weather.forecast_days.iter() {
+            rows.push(vec![
+                d.date.format("%Y-%m-%d").to_string(),
+                location.clone(),
+                u.temp_value(d.day.mintemp_c).to_string(),
+                u.temp_value(d.day.maxtemp_c).to_string(),
+                d.day.condition.text.trim().to_string(),
+                d.day.daily_chance_of_rain.to_string(),
+                u.wind_value(d.day.maxwind_kph).to_string(),
+            ]);
+        }
+        rows
+    }
+
+    /// `regn hourly` gives the next --hours hours, --hourly every hour of
+    /// the forecast days. times are in the --tz display timezone.
+    fn hour_rows(&self) -> Vec<Vec<String>> {
+        let u = self.args.units;
+        let location = self.location_name();
+        let mut rows = vec![vec![
+            "date".to_string(),
+            "time".to_string(),
+            "location".to_string(),
+            format!("temp_{}", u.temp.name()),
+            "condition".to_string(),
+            "chance_of_rain_pct".to_string(),
+            format!("wind_{}", u.wind.name()),
+        ]];
+        let hours: Vec<&RespHour> = match self.args.command {
+            Command::Hourly => self.upcoming_hours().collect(),
+            _ => self
+                .weather
+                .forecast_days
+                .iter()
+                .flat_map(|d| d.hour.iter())
+                .collect(),
+        };
+        for h in hours {
+            let Some(t) = DateTime::from_timestamp(h.time_epoch, 0) else {
+                continue;
+            };
+            let (wall, _) = self.args.time_zone.wall_clock(t, &self.weather.zone);
+            rows.push(vec![
+                wall.format("%Y-%m-%d").to_string(),
+                wall.format("%H:%M").to_string(),
+                location.clone(),
+                u.temp_value(h.temp_c).to_string(),
+                h.condition.text.trim().to_string(),
+                h.chance_of_rain.to_string(),
+                u.wind_value(h.wind_kph).to_string(),
+            ]);
+        }
+        rows
+    }
+}
+
+/// quotes csv fields when needed (RFC 4180). tsv has no quoting, so
+/// tabs and line breaks are replaced instead.
+fn escape_field(f: &str, sep: char) -> String {
+    if sep == '\t' {
+        return f.replace(['\t', '\n', '\r'], " ");
+    }
+    if f.contains([sep, '"', '\n', '\r']) {
+        format!("\"{}\"", f.replace('"', "\"\""))
+    } else {
+        f.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_escaping() {
+        assert_eq!(escape_field("Sunny", ','), "Sunny");
+        assert_eq!(escape_field("Paris, France", ','), "\"Paris, France\"");
+        assert_eq!(escape_field("6\" of snow", ','), "\"6\"\" of snow\"");
+        assert_eq!(escape_field("a\nb", ','), "\"a\nb\"");
+    }
+
+    #[test]
+    fn tsv_escaping() {
+        assert_eq!(escape_field("Paris, France", '\t'), "Paris, France");
+        assert_eq!(escape_field("a\tb\r\nc", '\t'), "a b  c");
+        assert_eq!(escape_field("6\" of snow", '\t'), "6\" of snow");
+    }
+}
@@ -0,0 +1,244 @@
+use std::fmt::{Display, Write};
+
+use chrono::{
+    DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, Offset,
+    TimeZone, Utc,
+};
+use chrono_tz::Tz;
+use serde::{Deserialize, Deserializer};
+
+pub const DEF_DATE_FORMAT: &str = "%a %d %b";
+const TIME_FORMAT_24H: &str = "%H:%M";
+const TIME_FORMAT_12H: &str = "%-I:%M %p";
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Clock {
+    H12,
+    H24,
+}
+
+impl Clock {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "12" | "12h" => Some(Self::H12),
+            "24" | "24h" => Some(Self::H24),
+            _ => None,
+        }
+    }
+}
+
+/// strftime-style format strings used when rendering dates and times
+#[derive(PartialEq, Clone, Debug)]
+pub struct TimeFormat {
+    pub date: String,
+    pub time: String,
+}
+
+impl TimeFormat {
+    /// an explicit time format wins over the 12/24-hour clock preset
+    pub fn new(date: Option<String>, time: Option<String>, clock: Clock) -> Self {
+        let clock_fmt = match clock {
+            Clock::H12 => TIME_FORMAT_12H,
+            Clock::H24 => TIME_FORMAT_24H,
+        };
+        Self {
+            date: date.unwrap_or(DEF_DATE_FORMAT.to_string()),
+            time: time.unwrap_or(clock_fmt.to_string()),
+        }
+    }
+
+    pub fn date(&self, d: NaiveDate) -> String {
+        render(d.format(&self.date))
+            .unwrap_or_else(|| d.format(DEF_DATE_FORMAT).to_string())
+    }
+
+    pub fn time(&self, t: NaiveTime) -> String {
+        render(t.format(&self.time))
+            .unwrap_or_else(|| t.format(TIME_FORMAT_24H).to_string())
+    }
+
+    pub fn date_time(&self, dt: NaiveDateTime) -> String {
+        format!("{} {}", self.date(dt.date()), self.time(dt.time()))
+    }
+
+    /// "Today" / "Tomorrow" relative to the location's own date,
+    /// otherwise the configured date format
+    pub fn day_label(&self, d: NaiveDate, today: NaiveDate) -> String {
+        match (d - today).num_days() {
+            0 => "Today".to_string(),
+            1 => "Tomorrow".to_string(),
+            _ => self.date(d),
+        }
+    }
+}
+
+impl Default for TimeFormat {
+    fn default() -> Self {
+        Self::new(None, None, Clock::H24)
+    }
+}
+
+/// the timezone that hourly and astronomy times are rendered in
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum DisplayZone {
+    /// the looked up location's own timezone
+    Location,
+    /// the timezone of the machine running regn
+    Local,
+    Utc,
+    Named(Tz),
+}
+
+impl DisplayZone {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "location" | "remote" => Some(Self::Location),
+            "local" => Some(Self::Local),
+            "utc" => Some(Self::Utc),
+            _ => s.parse::<Tz>().ok().map(Self::Named),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Location => "location",
+            Self::Local => "local",
+            Self::Utc => "utc",
+            Self::Named(tz) => tz.name(),
+        }
+    }
+
+    /// converts an instant into wall-clock time in this zone
+    pub fn wall_clock(
+        &self,
+        t: DateTime<Utc>,
+        loc: &LocationZone,
+    ) -> (NaiveDateTime, FixedOffset) {
+        let offset = match self {
+            Self::Location => loc.offset_at(t),
+            Self::Local => Local.offset_from_utc_datetime(&t.naive_utc()).fix(),
+            Self::Utc => Utc.fix(),
+            Self::Named(tz) => tz.offset_from_utc_datetime(&t.naive_utc()).fix(),
+        };
+        (t.with_timezone(&offset).naive_local(), offset)
+    }
+}
+
+/// timezone information of the looked up location
+#[derive(Clone, Debug)]
+pub struct LocationZone {
+    pub tz_id: String,
+    tz: Option<Tz>,
+    /// offset derived from the API's local time and epoch,
+    /// used when tz_id is not a known IANA name
+    fallback: FixedOffset,
+}
+
+impl LocationZone {
+    pub fn new(tz_id: &str, localtime: NaiveDateTime, epoch: i64) -> Self {
+        // localtime only has minute precision, round to whole minutes
+        let secs = localtime.and_utc().timestamp() - epoch;
+        let secs = ((secs as f64 / 60.0).round() * 60.0) as i32;
+        Self {
+            tz_id: tz_id.to_string(),
+            tz: tz_id.parse::<Tz>().ok(),
+            fallback: FixedOffset::east_opt(secs).unwrap_or(Utc.fix()),
+        }
+    }
+
+    pub fn offset_at(&self, t: DateTime<Utc>) -> FixedOffset {
+        match self.tz {
+            Some(tz) => tz.offset_from_utc_datetime(&t.naive_utc()).fix(),
+            None => self.fallback,
+        }
+    }
+
+    /// interprets a wall-clock time at the location as an instant
+    pub fn to_utc(&self, naive: NaiveDateTime) -> DateTime<Utc> {
+        let offset = match self.tz {
+            Some(tz) => tz
+                .offset_from_local_datetime(&naive)
+                .earliest()
+                .map(|o| o.fix())
+                .unwrap_or(self.fallback),
+            None => self.fallback,
+        };
+        (naive - offset).and_utc()
+    }
+}
+
+/// "UTC+09:00"
+pub fn fmt_utc_offset(o: FixedOffset) -> String {
+    let secs = o.local_minus_utc();
+    let sign = if secs < 0 { '-' } else { '+' };
+    let mins = secs.abs() / 60;
+    format!("UTC{}{:02}:{:02}", sign, mins / 60, mins % 60)
+}
+
+/// a formatted value, or None when the format asks for fields the
+/// value doesn't have (like "%H" for a date)
+fn render(formatted: impl Display) -> Option<String> {
+    let mut s = String::new();
+    write!(s, "{formatted}").ok()?;
+    Some(s)
+}
+
+/// returns true if chrono can render a date with the format string
+pub fn is_valid_date_format(fmt: &str) -> bool {
+    NaiveDate::from_ymd_opt(2026, 1, 1).is_some_and(|d| render(d.format(fmt)).is_some())
+}
+
+/// returns true if chrono can render a time with the format string
+pub fn is_valid_time_format(fmt: &str) -> bool {
+    NaiveTime::from_hms_opt(12, 0, 0).is_some_and(|t| render(t.format(fmt)).is_some())
+}
+
+/// WeatherAPI sends local times as "2026-10-18 9:05"
+pub fn de_local_datetime<'de, D>(d: D) -> Result<NaiveDateTime, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(d)?;
+    NaiveDateTime::parse_from_str(s.trim(), "%Y-%m-%d %H:%M")
+        .map_err(serde::de::Error::custom)
+}
+
+/// WeatherAPI sends astronomy times as "07:12 AM", or e.g. "No sunrise"
+/// during polar day and night
+pub fn de_astro_time<'de, D>(d: D) -> Result<Option<NaiveTime>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(d)?;
+    Ok(NaiveTime::parse_from_str(s.trim(), "%I:%M %p").ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn date_formats() {
+        assert!(is_valid_date_format("%a %d %b"));
+        assert!(is_valid_date_format("%Y-%m-%d"));
+        assert!(!is_valid_date_format("%H:%M"));
+        assert!(!is_valid_date_format("%Q"));
+    }
+
+    #[test]
+    fn time_formats() {
+        assert!(is_valid_time_format("%H:%M"));
+        assert!(is_valid_time_format("%-I:%M %p"));
+        assert!(!is_valid_time_format("%Y"));
+        assert!(!is_valid_time_format("%Q"));
+    }
+
+    #[test]
+    fn bad_formats_fall_back() {
+        let tf = TimeFormat::new(Some("%H".to_string()), Some("%Y".to_string()), Clock::H24);
+        let d = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        let t = NaiveTime::from_hms_opt(9, 5, 0).unwrap();
+        assert_eq!(tf.date(d), "Sun 18 Oct");
+        assert_eq!(tf.time(t), "09:05");
+    }
+}
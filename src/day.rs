@@ -0,0 +1,156 @@
+use std::io;
+
+use chrono::{DateTime, NaiveTime, Timelike};
+use crossterm::{
+    QueueableCommand,
+    style::{Color, SetForegroundColor},
+};
+
+use crate::{
+    ProgState, Regn, WeatherFrame,
+    api::{
+        api_main::{CurrentCondition, parse_current_weather},
+        request::RespForecastDay,
+    },
+    datetime::DisplayZone,
+    layout::Panel,
+};
+
+/// lines of the day view besides the hours, borders included
+const DAY_VIEW_OTHER_LINES: usize = 18;
+/// hours summed up for the precipitation of each part of the day
+const DAY_PARTS: [(&str, u32); 4] = [("night", 0), ("morning", 6), ("afternoon", 12), ("evening", 18)];
+
+impl Regn {
+    /// the forecast day under the cursor
+    pub fn selected_forecast_day(&self) -> Option<&RespForecastDay> {
+        self.weather.forecast_days.get(self.selected_day)
+    }
+
+    /// moves the cursor over the forecast days, without wrapping
+    pub fn move_day_cursor(&mut self, down: bool) -> io::Result<()> {
+        let last = self.weather.forecast_days.len().saturating_sub(1);
+        self.selected_day = match down {
+            true => (self.selected_day + 1).min(last),
+            false => self.selected_day.saturating_sub(1),
+        };
+        if self.prog_state == ProgState::Main {
+            self.focus = Panel::Daily;
+            self.details_for = Panel::Daily;
+        }
+        // the day view's animation follows the day
+        self.precipitation.clear();
+        self.util_clear_screen()
+    }
+
+    /// opens or closes the detail view for the selected day
+    pub fn toggle_day_view(&mut self) -> io::Result<()> {
+        self.prog_state = match self.prog_state {
+            ProgState::Day => ProgState::Main,
+            _ => ProgState::Day,
+        };
+        self.precipitation.clear();
+        self.util_clear_screen()
+    }
+
+    /// what the background animation shows: the selected day's weather
+    /// in the day view, else the current weather
+    pub fn shown_condition(&self) -> CurrentCondition {
+        match (&self.prog_state, self.selected_forecast_day()) {
+            (ProgState::Day, Some(day)) => parse_current_weather(day.day.condition.text.clone()),
+            _ => self.weather.current_condition,
+        }
+    }
+
+    /// the selected day in a frame of its own
+    pub fn day_view(&mut self) -> io::Result<()> {
+        let Some(day) = self.selected_forecast_day() else {
+            self.prog_state = ProgState::Main;
+            return Ok(());
+        };
+        let max_hours = (self.view_rows() as usize).saturating_sub(DAY_VIEW_OTHER_LINES);
+        let mut f = WeatherFrame::from_lines(self.format_day_view(day, max_hours));
+        if !f.fits(self.columns, self.view_rows()) {
+            return self.too_small();
+        }
+        f.make_centered(self.columns, self.view_rows());
+
+        self.sout
+            .queue(SetForegroundColor(self.args.theme.frame_color()))?;
+        self.w_rect(&f)?;
+        self.w_text(f)?;
+        self.sout.queue(SetForegroundColor(Color::Reset))?;
+        Ok(())
+    }
+
+    /// the day's summary, precipitation by part of the day, astronomy
+    /// and the hours, every second or third one when not all fit in
+    /// `max_hours` lines
+    fn format_day_view(&self, day: &RespForecastDay, max_hours: usize) -> Vec<String> {
+        let u = self.args.units;
+        let tf = &self.args.time_format;
+        let d = &day.day;
+
+        let mut s = vec![format!("{}, {}", self.weather.location.name, self.weather.location.country)];
+        s.extend(self.format_day_details(day));
+        s.push(format!("UV index {:.0}", d.uv));
+
+        let parts: Vec<String> = DAY_PARTS
+            .iter()
+            .enumerate()
+            .map(|(i, (name, from))| {
+                let to = DAY_PARTS.get(i + 1).map_or(24, |p| p.1);
+                let mm: f64 = day
+                    .hour
+                    .iter()
+                    .filter(|h| {
+                        DateTime::from_timestamp(h.time_epoch, 0)
+                            .map(|t| DisplayZone::Location.wall_clock(t, &self.weather.zone).0.hour())
+                            .is_some_and(|hour| (*from..to).contains(&hour))
+                    })
+                    .map(|h| h.precip_mm)
+                    .sum();
+                format!("{name} {}", u.precip(mm))
+            })
+            .collect();
+        s.push(format!("Precip. {}", parts.join(", ")));
+        if d.totalsnow_cm > 0.0 {
+            s.push(format!("Snow {}", u.precip(d.totalsnow_cm * 10.0)));
+        }
+        let moon = |t: Option<NaiveTime>| match t {
+            Some(t) => tf.time(self.display_time(day.date, t).0.time()),
+            None => "none".to_string(),
+        };
+        s.push(format!(
+            "Moonrise {}, moonset {}, {}",
+            moon(day.astro.moonrise),
+            moon(day.astro.moonset),
+            day.astro.moon_phase
+        ));
+
+        s.push(String::new());
+        let step = match max_hours {
+            0 => day.hour.len().max(1),
+            n => day.hour.len().div_ceil(n).max(1),
+        };
+        for h in day.hour.iter().step_by(step) {
+            let Some(t) = DateTime::from_timestamp(h.time_epoch, 0) else {
+                continue;
+            };
+            let (wall, _) = self.args.time_zone.wall_clock(t, &self.weather.zone);
+            s.push(format!(
+                "{}: {}, {}% rain, {}, {}, UV {:.0} ({})",
+                tf.time(wall.time()),
+                u.temp(h.temp_c),
+                h.chance_of_rain,
+                u.precip(h.precip_mm),
+                u.wind(h.wind_kph),
+                h.uv,
+                h.condition.text.trim()
+            ));
+        }
+        s.push(String::new());
+        s.push("[↑] [↓] other days, [Enter] back".to_string());
+        s
+    }
+}
@@ -0,0 +1,115 @@
+use std::{fmt::Write, path::Path};
+
+use crate::{
+    api::api_main::{WeatherAPI, api_main},
+    arg::Arguments,
+    serve::serve,
+};
+
+const OPENMETRICS_CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// `regn export --openmetrics [--output <path> | --serve <addr>]`
+pub fn run_export(args: &Arguments) -> Result<(), String> {
+    if !args.openmetrics {
+        return Err("Choose an export format: regn export --openmetrics".to_string());
+    }
+    let render = || {
+        api_main(
+            &args.provider,
+            &args.key_source,
+            &args.location,
+            &args.forecast,
+            args.max_age,
+        )
+        .map(|w| openmetrics(&w))
+    };
+
+    if let Some(addr) = args.serve {
+        return serve(addr, "/metrics", OPENMETRICS_CONTENT_TYPE, render);
+    }
+    let text = render()?;
+    match &args.out_path {
+        Some(path) => write_textfile(path, &text),
+        None => {
+            print!("{text}");
+            Ok(())
+        }
+    }
+}
+
+/// the textfile collector may read at any moment, so the file is
+/// written next to the target and renamed into place
+pub fn write_textfile(path: &Path, text: &str) -> Result<(), String> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, text)
+        .and_then(|_| std::fs::rename(&tmp, path))
+        .map_err(|e| format!("Could not write {}: {e}", path.display()))
+}
+
+/// gauges in base units, labelled with the location.
+/// forecast gauges also get the day offset, 0 being today.
+pub fn openmetrics(w: &WeatherAPI) -> String {
+    let loc = format!(
+        "location=\"{}\",country=\"{}\"",
+        escape_label(&w.location.name),
+        escape_label(&w.location.country)
+    );
+    let mut s = String::new();
+    let mut gauge = |name: &str, help: &str, samples: Vec<(String, f64)>| {
+        let _ = writeln!(s, "# TYPE {name} gauge");
+        let _ = writeln!(s, "# HELP {name} {help}");
+        for (labels, v) in samples {
+            let _ = writeln!(s, "{name}{{{labels}}} {v}");
+        }
+    };
+    let now = |v: f64| vec![(loc.clone(), v)];
+    let per_day = |f: &dyn Fn(usize) -> f64| {
+        (0..w.forecast_days.len())
+            .map(|i| (format!("{loc},day=\"{i}\""), f(i)))
+            .collect::<Vec<_>>()
+    };
+    let days = &w.forecast_days;
+
+    gauge("regn_temperature_celsius", "Current air temperature.", now(w.current_temp_c));
+    gauge("regn_feels_like_celsius", "Current feels-like temperature.", now(w.current_feelslike_c));
+    gauge("regn_humidity_ratio", "Current relative humidity.", now(w.current_humidity / 100.0));
+    gauge(
+        "regn_wind_speed_meters_per_second",
+        "Current wind speed.",
+        now(w.current_wind_kph / 3.6),
+    );
+    gauge("regn_precipitation_meters", "Current precipitation.", now(w.current_precip_mm / 1000.0));
+    gauge("regn_pressure_pascals", "Current air pressure.", now(w.current_pressure_mb * 100.0));
+    gauge(
+        "regn_forecast_max_temperature_celsius",
+        "Forecast maximum temperature, by day offset.",
+        per_day(&|i| days[i].day.maxtemp_c),
+    );
+    gauge(
+        "regn_forecast_min_temperature_celsius",
+        "Forecast minimum temperature, by day offset.",
+        per_day(&|i| days[i].day.mintemp_c),
+    );
+    gauge(
+        "regn_forecast_max_wind_speed_meters_per_second",
+        "Forecast maximum wind speed, by day offset.",
+        per_day(&|i| days[i].day.maxwind_kph / 3.6),
+    );
+    gauge(
+        "regn_forecast_rain_chance_ratio",
+        "Forecast chance of rain, by day offset.",
+        per_day(&|i| days[i].day.daily_chance_of_rain as f64 / 100.0),
+    );
+    gauge(
+        "regn_last_update_timestamp_seconds",
+        "When the data was fetched from the provider.",
+        now(w.fetched_at.timestamp() as f64),
+    );
+    s.push_str("# EOF\n");
+    s
+}
+
+fn escape_label(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
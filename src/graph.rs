@@ -0,0 +1,248 @@
+use std::io;
+
+use crossterm::{
+    QueueableCommand,
+    cursor::MoveTo,
+    style::{Color, Print, SetForegroundColor},
+};
+
+use crate::{Pos, Regn, WeatherFrame, chart::axis};
+
+/// dots per braille cell
+const DOTS_W: usize = 2;
+const DOTS_H: usize = 4;
+/// bit for each dot of a cell, by [row][column]
+const DOT_BITS: [[u8; DOTS_W]; DOTS_H] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+/// smallest panel worth drawing, borders included
+const MIN_HEIGHT: u16 = 8;
+const MAX_HEIGHT: u16 = 20;
+const MIN_PLOT_WIDTH: usize = 12;
+
+/// what a dot belongs to. later layers are drawn over earlier ones
+/// when they share a cell.
+#[derive(PartialEq, PartialOrd, Clone, Copy)]
+pub enum Layer {
+    Separator,
+    FeelsLike,
+    Temp,
+    Now,
+}
+
+/// a grid of braille cells, drawn on dot by dot
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    bits: Vec<u8>,
+    layers: Vec<Option<Layer>>,
+}
+
+impl Canvas {
+    /// `width` and `height` are in cells
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            bits: vec![0; width * height],
+            layers: vec![None; width * height],
+        }
+    }
+
+    pub fn dots_wide(&self) -> usize {
+        self.width * DOTS_W
+    }
+
+    pub fn dots_high(&self) -> usize {
+        self.height * DOTS_H
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, layer: Layer) {
+        if x >= self.dots_wide() || y >= self.dots_high() {
+            return;
+        }
+        let i = (y / DOTS_H) * self.width + x / DOTS_W;
+        self.bits[i] |= DOT_BITS[y % DOTS_H][x % DOTS_W];
+        if self.layers[i].is_none_or(|l| l < layer) {
+            self.layers[i] = Some(layer);
+        }
+    }
+
+    /// a straight line, one dot per step along the longer axis
+    pub fn line(&mut self, (x0, y0): (usize, usize), (x1, y1): (usize, usize), layer: Layer) {
+        let steps = x0.abs_diff(x1).max(y0.abs_diff(y1)).max(1);
+        for step in 0..=steps {
+            let t = step as f64 / steps as f64;
+            let x = x0 as f64 + (x1 as f64 - x0 as f64) * t;
+            let y = y0 as f64 + (y1 as f64 - y0 as f64) * t;
+            self.set(x.round() as usize, y.round() as usize, layer);
+        }
+    }
+
+    /// a dotted vertical line, every other dot
+    pub fn dotted_column(&mut self, x: usize, layer: Layer) {
+        for y in (0..self.dots_high()).step_by(2) {
+            self.set(x, y, layer);
+        }
+    }
+
+    /// the character and layer of every cell in a row
+    pub fn row(&self, row: usize) -> impl Iterator<Item = (char, Option<Layer>)> + '_ {
+        (0..self.width).map(move |col| {
+            let i = row * self.width + col;
+            let c = char::from_u32(0x2800 + self.bits[i] as u32).unwrap_or(' ');
+            (c, self.layers[i])
+        })
+    }
+}
+
+impl Regn {
+    /// rows taken by the graph at the bottom of the TUI, or 0 when it
+    /// is hidden or there is no room for it next to `other_height`
+    pub fn graph_height(&self, other_height: u16) -> u16 {
+        if !self.show_graph {
+            return 0;
+        }
+        let h = (self.view_rows() / 3).clamp(MIN_HEIGHT, MAX_HEIGHT);
+        match self.view_rows() >= other_height + h {
+            true => h,
+            false => 0,
+        }
+    }
+
+    /// hourly temperature and feels-like for the whole forecast, in
+    /// braille across the full width of the terminal
+    pub fn temp_graph(&mut self, height: u16) -> io::Result<()> {
+        let u = self.args.units;
+        let hours: Vec<(i64, f64, f64)> = self
+            .weather
+            .forecast_days
+            .iter()
+            .flat_map(|d| d.hour.iter())
+            .map(|h| (h.time_epoch, h.temp_c, h.feelslike_c))
+            .collect();
+        let (Some(first), Some(last)) = (hours.first(), hours.last()) else {
+            return Ok(());
+        };
+        let (t0, t1) = (first.0, last.0.max(first.0 + 1));
+
+        let lo = hours.iter().map(|h| h.1.min(h.2)).fold(f64::INFINITY, f64::min).floor();
+        let hi = hours.iter().map(|h| h.1.max(h.2)).fold(f64::NEG_INFINITY, f64::max).ceil();
+        let hi = hi.max(lo + 1.0);
+        let plot_h = height.saturating_sub(4) as usize;
+        if plot_h == 0 {
+            return Ok(());
+        }
+
+        // top, middle and bottom rows are labelled with the value at
+        // the middle of the row
+        let max_y = (plot_h * DOTS_H - 1) as f64;
+        let row_value = |row: usize| hi - ((row * DOTS_H) as f64 + 1.5) / max_y * (hi - lo);
+        let y_labels = [u.temp(hi), u.temp(row_value(plot_h / 2)), u.temp(lo)];
+        let label_w = y_labels.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+
+        // border, padding, labels and the axis take the rest
+        let plot_w = (self.columns as usize).saturating_sub(label_w + 5);
+        if plot_w < MIN_PLOT_WIDTH {
+            return Ok(());
+        }
+
+        let mut canvas = Canvas::new(plot_w, plot_h);
+        let max_x = (canvas.dots_wide() - 1) as f64;
+        let x_at = |t: i64| ((t - t0) as f64 / (t1 - t0) as f64 * max_x).round() as usize;
+        let y_at = |v: f64| ((hi - v) / (hi - lo) * max_y).round() as usize;
+
+        // day separators, with the day names under the axis, as long as
+        // the space between separators allows
+        let days = &self.weather.forecast_days;
+        let day_w = plot_w / days.len().max(1);
+        let mut day_labels = Vec::new();
+        for day in days.iter() {
+            let Some(h) = day.hour.first() else { continue };
+            let x = x_at(h.time_epoch);
+            if x > 0 {
+                canvas.dotted_column(x, Layer::Separator);
+            }
+            let label = [self.args.time_format.date(day.date), day.date.format("%a %d").to_string()]
+                .into_iter()
+                .find(|l| l.chars().count() < day_w)
+                .unwrap_or_else(|| day.date.format("%a").to_string());
+            day_labels.push((x / DOTS_W, label));
+        }
+
+        for pair in hours.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            canvas.line((x_at(a.0), y_at(a.2)), (x_at(b.0), y_at(b.2)), Layer::FeelsLike);
+            canvas.line((x_at(a.0), y_at(a.1)), (x_at(b.0), y_at(b.1)), Layer::Temp);
+        }
+
+        let now = self.weather.now.timestamp();
+        let now_col = (t0..=t1).contains(&now).then(|| {
+            let x = x_at(now);
+            canvas.line((x, 0), (x, max_y as usize), Layer::Now);
+            x / DOTS_W
+        });
+
+        // the panel, across the bottom of the screen
+        let mut frame = WeatherFrame::new();
+        frame.width = self.columns;
+        frame.height = height;
+        frame.pos = Pos::new(0, self.view_rows() - height);
+        let (temp_color, feels_color, now_color) = self.args.theme.graph_colors();
+        let frame_color = self.args.theme.frame_color();
+        self.sout.queue(SetForegroundColor(frame_color))?;
+        self.w_rect(&frame)?;
+
+        let top = frame.pos.row;
+        self.sout.queue(MoveTo(2, top))?;
+        self.sout.queue(Print(" "))?;
+        self.sout.queue(SetForegroundColor(temp_color))?;
+        self.sout.queue(Print("⣀ temperature "))?;
+        self.sout.queue(SetForegroundColor(feels_color))?;
+        self.sout.queue(Print("⣀ feels like "))?;
+
+        let plot_col = (label_w + 3) as u16;
+        for row in 0..plot_h {
+            let (label, tick) = match row {
+                0 => (y_labels[0].as_str(), '┤'),
+                _ if row == plot_h / 2 => (y_labels[1].as_str(), '┤'),
+                _ if row == plot_h - 1 => (y_labels[2].as_str(), '┤'),
+                _ => ("", '│'),
+            };
+            self.sout.queue(SetForegroundColor(frame_color))?;
+            self.sout.queue(MoveTo(2, top + 1 + row as u16))?;
+            self.sout.queue(Print(format!("{label:>label_w$}{tick}")))?;
+            for (c, layer) in canvas.row(row) {
+                let color = match layer {
+                    Some(Layer::Temp) => temp_color,
+                    Some(Layer::FeelsLike) => feels_color,
+                    Some(Layer::Now) => now_color,
+                    Some(Layer::Separator) | None => frame_color,
+                };
+                self.sout.queue(SetForegroundColor(color))?;
+                self.sout.queue(Print(c))?;
+            }
+        }
+
+        // x axis, with a tick under every day separator and "now"
+        let mut x_axis: Vec<char> = vec!['─'; plot_w];
+        for (col, _) in day_labels.iter().filter(|(col, _)| *col > 0) {
+            x_axis[(*col).min(plot_w - 1)] = '┴';
+        }
+        self.sout.queue(SetForegroundColor(frame_color))?;
+        self.sout.queue(MoveTo(2, top + 1 + plot_h as u16))?;
+        self.sout
+            .queue(Print(format!("{:>label_w$}└{}", "", x_axis.into_iter().collect::<String>())))?;
+
+        let labels = axis(&day_labels);
+        let labels: String = labels.chars().take(plot_w).collect();
+        self.sout.queue(MoveTo(plot_col, top + 2 + plot_h as u16))?;
+        self.sout.queue(Print(format!("{labels:<plot_w$}")))?;
+        if let Some(col) = now_col {
+            let col = (col.min(plot_w.saturating_sub(3))) as u16;
+            self.sout.queue(SetForegroundColor(now_color))?;
+            self.sout.queue(MoveTo(plot_col + col, top + 1 + plot_h as u16))?;
+            self.sout.queue(Print("now"))?;
+        }
+        self.sout.queue(SetForegroundColor(Color::Reset))?;
+        Ok(())
+    }
+}
@@ -1,4 +1,7 @@
-use crate::Regn;
+use crate::{
+    arg::{COMMANDS, CmdSpec, OPTIONS, OptSpec},
+    controls::KEYS,
+};
 
 const HELP_CONTENTS: &str = include_str!("static/help.txt");
 
@@ -8,15 +11,167 @@ pub const APP_REPO: &str = env!("CARGO_PKG_REPOSITORY");
 pub const APP_DESC: &str = env!("CARGO_PKG_DESCRIPTION");
 pub const APP_AUTH: &str = env!("CARGO_PKG_AUTHORS");
 
-impl Regn {
-    pub fn print_help(&mut self) {
-        println!("");
-        println!("{n} v{v}", n = APP_NAME, v = APP_VERS);
-        println!("{APP_AUTH}");
-        println!("{APP_REPO}");
-        println!("{APP_DESC}");
-        println!("==========");
-        print!("{}", HELP_CONTENTS);
+/// the command and option sections are generated from the definitions
+/// in arg.rs so they can't drift from what the parser accepts
+pub fn print_help(topic: Option<&CmdSpec>) {
+    match topic {
+        Some(cmd) => print!("{}", command_help(cmd)),
+        None => {
+            println!();
+            println!("{n} v{v}", n = APP_NAME, v = APP_VERS);
+            println!("{APP_AUTH}");
+            println!("{APP_REPO}");
+            println!("{APP_DESC}");
+            println!("==========");
+            print!("{}", general_help());
+        }
     }
 }
 
+pub fn general_help() -> String {
+    let commands: Vec<String> = COMMANDS.iter().map(command_line).collect();
+    let options: Vec<String> = OPTIONS.iter().map(option_line).collect();
+    let controls: Vec<String> = KEYS.iter().map(|(keys, what)| format!("{keys} : {what}")).collect();
+    HELP_CONTENTS
+        .replace("{commands}", &commands.join("\n"))
+        .replace("{options}", &options.join("\n"))
+        .replace("{controls}", &controls.join("\n"))
+}
+
+pub fn command_help(cmd: &CmdSpec) -> String {
+    let mut s = format!("Usage: {APP_NAME} {}", usage_name(cmd));
+    s.push_str(" [options]\n");
+    s.push_str(&format!("{}\n\nOptions\n", capitalize(cmd.about)));
+    for opt in cmd.option_specs() {
+        s.push_str(&option_line(opt));
+        s.push('\n');
+    }
+    s
+}
+
+/// `regn man`
+pub fn print_man_page() -> Result<(), String> {
+    print!("{}", man_page());
+    Ok(())
+}
+
+/// a roff man page built from the same definitions and help text
+pub fn man_page() -> String {
+    let mut s = format!(
+        ".TH {} 1 \"\" \"{APP_NAME} {APP_VERS}\" \"User Commands\"\n",
+        APP_NAME.to_uppercase()
+    );
+    s.push_str(&format!(".SH NAME\n{APP_NAME} \\- {}\n", roff(APP_DESC)));
+    s.push_str(&format!(
+        ".SH SYNOPSIS\n.B {APP_NAME}\n[\\fIcommand\\fR] [\\fIoptions\\fR]\n"
+    ));
+
+    s.push_str(".SH DESCRIPTION\n");
+    let intro = HELP_CONTENTS.split("\nUsage\n").next().unwrap_or("");
+    s.push_str(&roff_lines(intro));
+
+    s.push_str(".SH COMMANDS\n");
+    for cmd in COMMANDS {
+        s.push_str(&format!(".TP\n.B {}\n{}\n", roff(&usage_name(cmd)), roff(cmd.about)));
+    }
+
+    s.push_str(".SH OPTIONS\n");
+    for opt in OPTIONS {
+        let mut flags = match opt.short {
+            Some(c) => format!("\\fB\\-{c}\\fR, \\fB\\-\\-{}\\fR", roff(opt.long)),
+            None => format!("\\fB\\-\\-{}\\fR", roff(opt.long)),
+        };
+        if let Some(v) = opt.value {
+            flags.push_str(&format!(" \\fI<{v}>\\fR"));
+        }
+        s.push_str(&format!(".TP\n{flags}\n{}\n", roff(opt.help)));
+    }
+
+    s.push_str(".SH EXAMPLES\n");
+    s.push_str(&roff_lines(section(HELP_CONTENTS, "Example usage:\n", "\n\n")));
+
+    s.push_str(".SH TEMPLATES\n");
+    s.push_str(&roff_lines(section(HELP_CONTENTS, "Templates\n", "\n\n")));
+
+    s.push_str(".SH CONFIGURATION\n");
+    s.push_str(&roff_lines(section(HELP_CONTENTS, "Configuration\n", "\nControls")));
+
+    s.push_str(".SH CONTROLS\n");
+    for (keys, what) in KEYS {
+        s.push_str(&format!(".TP\n.B {}\n{}\n", roff(keys), roff(what)));
+    }
+
+    s.push_str(".SH FILES\n");
+    s.push_str(".TP\n$XDG_CONFIG_HOME/regn/config.toml\nthe config file (~/.config/regn/config.toml)\n");
+    s.push_str(".TP\n$XDG_STATE_HOME/regn/history\nrecently used locations, offered by shell completion (~/.local/state/regn/history)\n");
+    s.push_str(".TP\n$XDG_CACHE_HOME/regn\ncached answers from the provider, see \\-\\-max\\-age (~/.cache/regn)\n");
+    s.push_str(".TP\n~/.regn\nthe API key, if not given any other way\n");
+
+    s.push_str(&format!(".SH AUTHOR\n{}\n", roff(APP_AUTH)));
+    s.push_str(&format!(".SH SEE ALSO\n{}\n", roff(APP_REPO)));
+    s
+}
+
+/// the text between a heading and the next marker
+fn section<'a>(text: &'a str, start: &str, end: &str) -> &'a str {
+    let Some((_, rest)) = text.split_once(start) else {
+        return "";
+    };
+    rest.split(end).next().unwrap_or(rest)
+}
+
+/// escapes text for roff
+fn roff(s: &str) -> String {
+    s.replace('\\', "\\e").replace('-', "\\-")
+}
+
+/// keeps the line breaks of a block of text, blank lines become paragraphs
+fn roff_lines(text: &str) -> String {
+    let mut s = String::new();
+    for line in text.trim().lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            s.push_str(".PP\n");
+            continue;
+        }
+        // a leading dot or quote would be taken as a request
+        if line.starts_with('.') || line.starts_with('\'') {
+            s.push_str("\\&");
+        }
+        s.push_str(&roff(line));
+        s.push_str("\n.br\n");
+    }
+    s
+}
+
+/// "search <str> : look up locations matching a name"
+fn command_line(cmd: &CmdSpec) -> String {
+    format!("{} : {}", usage_name(cmd), cmd.about)
+}
+
+fn usage_name(cmd: &CmdSpec) -> String {
+    match cmd.args {
+        "" => cmd.name.to_string(),
+        args => format!("{} {args}", cmd.name),
+    }
+}
+
+/// "-l, --location <str> : choose city location ..."
+pub fn option_line(opt: &OptSpec) -> String {
+    let mut s = match opt.short {
+        Some(c) => format!("-{c}, --{}", opt.long),
+        None => format!("--{}", opt.long),
+    };
+    if let Some(v) = opt.value {
+        s.push_str(&format!(" <{v}>"));
+    }
+    format!("{s} : {}", opt.help)
+}
+
+fn capitalize(s: &str) -> String {
+    let mut c = s.chars();
+    match c.next() {
+        Some(first) => first.to_uppercase().chain(c).collect(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,41 @@
+use std::path::PathBuf;
+
+use crate::config::state_dir;
+
+/// how many recently used locations are remembered
+const MAX_HISTORY: usize = 20;
+
+fn history_path() -> PathBuf {
+    state_dir().join("history")
+}
+
+/// recently used locations, most recent first
+pub fn load_history() -> Vec<String> {
+    std::fs::read_to_string(history_path())
+        .map(|s| {
+            s.lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// moves the location to the top of the history. failing to write it
+/// is not worth bothering the user about.
+pub fn add_to_history(location: &str) {
+    let location = location.trim();
+    if location.is_empty() {
+        return;
+    }
+    let mut history = load_history();
+    history.retain(|l| !l.eq_ignore_ascii_case(location));
+    history.insert(0, location.to_string());
+    history.truncate(MAX_HISTORY);
+
+    let mut contents = history.join("\n");
+    contents.push('\n');
+    let _ = std::fs::create_dir_all(state_dir())
+        .and_then(|_| std::fs::write(history_path(), contents));
+}
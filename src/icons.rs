@@ -0,0 +1,64 @@
+use crate::api::api_main::CurrentCondition;
+
+/// small ASCII art shown next to the current conditions in text output.
+/// every line is padded to the same width.
+pub fn condition_icon(c: CurrentCondition) -> [&'static str; 5] {
+    match c {
+        CurrentCondition::Sun => [
+            r"    \   /    ",
+            r"     .-.     ",
+            r"  - (   ) -  ",
+            r"     `-'     ",
+            r"    /   \    ",
+        ],
+        CurrentCondition::Clear => [
+            r"     _..     ",
+            r"   .' .-'    ",
+            r"  /  (       ",
+            r"  \  '-.     ",
+            r"   '-..'     ",
+        ],
+        CurrentCondition::Cloud => [
+            r"             ",
+            r"     .--.    ",
+            r"  .-(    ).  ",
+            r" (___.__)__) ",
+            r"             ",
+        ],
+        CurrentCondition::Rain => [
+            r"     .-.     ",
+            r"    (   ).   ",
+            r"   (___(__)  ",
+            r"    ' ' ' '  ",
+            r"   ' ' ' '   ",
+        ],
+        CurrentCondition::Snow => [
+            r"     .-.     ",
+            r"    (   ).   ",
+            r"   (___(__)  ",
+            r"    *  *  *  ",
+            r"   *  *  *   ",
+        ],
+        CurrentCondition::Thunder => [
+            r"     .-.     ",
+            r"    (   ).   ",
+            r"   (___(__)  ",
+            r"    /_  /_   ",
+            r"     /   /   ",
+        ],
+        CurrentCondition::Fog => [
+            r"             ",
+            r" _ - _ - _ - ",
+            r"  _ - _ - _  ",
+            r" _ - _ - _ - ",
+            r"             ",
+        ],
+        CurrentCondition::Unknown => [
+            r"    .-.      ",
+            r"     __)     ",
+            r"    (        ",
+            r"     `-'     ",
+            r"      *      ",
+        ],
+    }
+}
@@ -0,0 +1,190 @@
+use chrono::{NaiveDate, NaiveTime};
+
+use crate::{
+    api::api_main::{WeatherAPI, api_main, parse_current_weather},
+    arg::Arguments,
+    datetime::fmt_utc_offset,
+    export::write_textfile,
+    help::{APP_NAME, APP_VERS},
+    serve::serve,
+};
+
+const ICS_CONTENT_TYPE: &str = "text/calendar; charset=utf-8";
+/// how often subscribed calendars should fetch the feed again
+const REFRESH_INTERVAL: &str = "PT3H";
+/// longest content line allowed by RFC 5545, in octets
+const MAX_LINE: usize = 75;
+
+/// `regn ics [--output <path> | --serve <addr>]`
+pub fn run_ics(args: &Arguments) -> Result<(), String> {
+    let render = || {
+        api_main(
+            &args.provider,
+            &args.key_source,
+            &args.location,
+            &args.forecast,
+            args.max_age,
+        )
+        .map(|w| ics(&w, args))
+    };
+
+    if let Some(addr) = args.serve {
+        return serve(addr, "/weather.ics", ICS_CONTENT_TYPE, render);
+    }
+    let text = render()?;
+    match &args.out_path {
+        Some(path) => write_textfile(path, &text),
+        None => {
+            print!("{text}");
+            Ok(())
+        }
+    }
+}
+
+/// a calendar with one all-day event per forecast day. the UIDs only
+/// depend on the place and the date, so calendar apps replace an old
+/// forecast for a day instead of adding another.
+pub fn ics(w: &WeatherAPI, args: &Arguments) -> String {
+    let u = args.units;
+    let tf = &args.time_format;
+    let place = format!("{}, {}", w.location.name, w.location.country);
+    let stamp = w.fetched_at.format("%Y%m%dT%H%M%SZ");
+    let uid_place = uid_part(&place);
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:-//{APP_NAME}//{APP_NAME} {APP_VERS}//EN"),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape_text(&format!("Weather in {place}"))),
+        format!("REFRESH-INTERVAL;VALUE=DURATION:{REFRESH_INTERVAL}"),
+        format!("X-PUBLISHED-TTL:{REFRESH_INTERVAL}"),
+    ];
+
+    for day in w.forecast_days.iter() {
+        let d = &day.day;
+        let text = d.condition.text.trim();
+        let summary = format!(
+            "{} {:.0}°/{:.0}° {}",
+            parse_current_weather(text.to_string()).icon(),
+            u.temp_value(d.maxtemp_c),
+            u.temp_value(d.mintemp_c),
+            text
+        );
+
+        // astronomy times in the chosen display timezone
+        let mut offset = None;
+        let mut at = |t: Option<NaiveTime>| match t {
+            Some(t) => {
+                let instant = w.zone.to_utc(day.date.and_time(t));
+                let (wall, off) = args.time_zone.wall_clock(instant, &w.zone);
+                offset = Some(off);
+                tf.time(wall.time())
+            }
+            None => "none".to_string(),
+        };
+        let sun = format!("Sunrise {}, sunset {}", at(day.astro.sunrise), at(day.astro.sunset));
+        let sun = match offset {
+            Some(off) => format!("{sun} ({})", fmt_utc_offset(off)),
+            None => sun,
+        };
+        let description = [
+            format!("{text} in {place}"),
+            format!("High {}, low {}", u.temp(d.maxtemp_c), u.temp(d.mintemp_c)),
+            format!("Chance of rain {}%, precip. {}", d.daily_chance_of_rain, u.precip(d.totalprecip_mm)),
+            format!("Wind up to {}", u.wind(d.maxwind_kph)),
+            sun,
+        ]
+        .join("\n");
+
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}-{uid_place}@{APP_NAME}", day.date.format("%Y%m%d")),
+            format!("DTSTAMP:{stamp}"),
+            format!("DTSTART;VALUE=DATE:{}", ics_date(day.date)),
+            format!("DTEND;VALUE=DATE:{}", ics_date(day.date.succ_opt().unwrap_or(day.date))),
+            format!("SUMMARY:{}", escape_text(&summary)),
+            format!("DESCRIPTION:{}", escape_text(&description)),
+            format!("LOCATION:{}", escape_text(&place)),
+            "TRANSP:TRANSPARENT".to_string(),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|l| fold(l)).collect()
+}
+
+fn ics_date(d: NaiveDate) -> String {
+    d.format("%Y%m%d").to_string()
+}
+
+/// "Rio de Janeiro, Brazil" gives "rio-de-janeiro-brazil"
+fn uid_part(s: &str) -> String {
+    s.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// TEXT values escape backslashes, separators and line breaks, which
+/// may also be "\r\n" or a lone "\r"
+fn escape_text(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace(['\r', '\n'], "\\n")
+}
+
+/// splits a content line into lines of at most 75 octets, continued
+/// with a leading space, without breaking a character apart
+fn fold(line: &str) -> String {
+    let mut out = String::new();
+    let mut len = 0;
+    for c in line.chars() {
+        if len + c.len_utf8() > MAX_LINE {
+            out.push_str("\r\n ");
+            len = 1;
+        }
+        out.push(c);
+        len += c.len_utf8();
+    }
+    out.push_str("\r\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_escaping() {
+        assert_eq!(escape_text("Sunny"), "Sunny");
+        assert_eq!(escape_text("Rain; 5 mm, windy"), "Rain\\; 5 mm\\, windy");
+        assert_eq!(escape_text("a\\b"), "a\\\\b");
+        assert_eq!(escape_text("a\nb\r\nc\rd"), "a\\nb\\nc\\nd");
+    }
+
+    #[test]
+    fn folding() {
+        assert_eq!(fold("SUMMARY:Sunny"), "SUMMARY:Sunny\r\n");
+
+        let long = "x".repeat(MAX_LINE + 10);
+        let folded = fold(&long);
+        let lines: Vec<&str> = folded.trim_end_matches("\r\n").split("\r\n").collect();
+        assert_eq!(lines[0].len(), MAX_LINE);
+        assert_eq!(lines[1], format!(" {}", "x".repeat(10)));
+
+        // "é" is two octets and would end at octet 76
+        let line = format!("{}é", "x".repeat(MAX_LINE - 1));
+        let folded = fold(&line);
+        for l in folded.split("\r\n") {
+            assert!(l.len() <= MAX_LINE, "{l}");
+        }
+        assert_eq!(folded, format!("{}\r\n é\r\n", "x".repeat(MAX_LINE - 1)));
+        assert_eq!(folded.replace("\r\n ", "").trim_end(), line);
+    }
+}
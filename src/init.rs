@@ -0,0 +1,246 @@
+use std::{
+    fs::File,
+    io::{self, IsTerminal, Write, stdout},
+    path::{Path, PathBuf},
+};
+
+use crossterm::{
+    event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, read},
+    terminal::{disable_raw_mode, enable_raw_mode},
+};
+use toml_edit::{Item, Table, value};
+
+use crate::{
+    api::{
+        api_main::{Provider, check_key_main},
+        key::key_from_command,
+    },
+    arg::DEF_LOCATION,
+    config::{config_dir, config_path, edit_config},
+    units::UnitSystem,
+};
+
+/// `regn init`, asks for the basics and writes them to the config file
+pub fn run_init() -> Result<(), String> {
+    println!("Setting up regn. Press Enter to accept the value in [brackets].\n");
+
+    let provider = loop {
+        let p = prompt("Weather provider (weatherapi)", Provider::WeatherApi.name())?;
+        match Provider::parse(&p) {
+            Some(p) => break p,
+            None => println!("  Unknown provider \"{p}\"."),
+        }
+    };
+
+    println!(
+        "\nGet a free API key at https://www.weatherapi.com/signup.aspx\n\
+         Paste the key, or leave it empty to give a command that prints it instead."
+    );
+    let (key, key_command) = loop {
+        let key = prompt_secret("API key")?;
+        if !key.is_empty() {
+            break (key, None);
+        }
+        let cmd = prompt("Key command (e.g. pass show weatherapi)", "")?;
+        if cmd.is_empty() {
+            continue;
+        }
+        match key_from_command(&cmd) {
+            Ok(k) => break (k, Some(cmd)),
+            Err(e) => println!("  {e}"),
+        }
+    };
+
+    print!("Checking the key... ");
+    stdout().flush().map_err(|e| e.to_string())?;
+    match check_key_main(&provider, &key) {
+        Ok(check) => {
+            println!("ok (test request for {} succeeded)", check.location);
+            if check.quota.is_empty() {
+                println!("  {} does not report plan or quota details.", provider.name());
+            }
+            for (k, v) in check.quota.iter() {
+                println!("  {k}: {v}");
+            }
+        }
+        Err(e) => {
+            println!("failed\n  {e}");
+            if !confirm("Save the key anyway?")? {
+                return Err("Setup aborted, nothing was written.".to_string());
+            }
+        }
+    }
+
+    println!();
+    let location = prompt("Default location", DEF_LOCATION)?;
+    let units = loop {
+        let u = prompt("Units (metric, imperial, uk)", "metric")?;
+        if UnitSystem::parse(&u).is_some() {
+            break u.to_lowercase();
+        }
+        println!("  Unknown unit system \"{u}\".");
+    };
+
+    let key_file = match &key_command {
+        Some(_) => None,
+        None => Some(write_key_file(provider, &key)?),
+    };
+
+    edit_config(|doc| {
+        doc["provider"] = value(provider.name());
+        doc["location"] = value(location.as_str());
+        doc["units"] = value(units.as_str());
+        let keys = doc
+            .entry("keys")
+            .or_insert(Item::Table(Table::new()))
+            .as_table_mut()
+            .ok_or(format!("\"keys\" in {} is not a table.", config_path().display()))?;
+        keys.set_implicit(true);
+        let mut t = Table::new();
+        match (&key_command, &key_file) {
+            (Some(cmd), _) => t["key_command"] = value(cmd.as_str()),
+            (None, Some(path)) => t["key_file"] = value(path.display().to_string()),
+            _ => {}
+        }
+        keys.insert(provider.name(), Item::Table(t));
+        Ok(())
+    })?;
+    restrict_permissions(&config_path())?;
+
+    println!("\nWrote {}", config_path().display());
+    if let Some(path) = key_file {
+        println!("Saved the key to {}", path.display());
+    }
+    println!("Try it with: regn now");
+    Ok(())
+}
+
+fn prompt(question: &str, default: &str) -> Result<String, String> {
+    if default.is_empty() {
+        print!("{question}: ");
+    } else {
+        print!("{question} [{default}]: ");
+    }
+    stdout().flush().map_err(|e| e.to_string())?;
+
+    let mut line = String::new();
+    let n = io::stdin()
+        .read_line(&mut line)
+        .map_err(|e| format!("Could not read input: {e}"))?;
+    if n == 0 {
+        return Err("Setup aborted, nothing was written.".to_string());
+    }
+    let line = line.trim();
+    Ok(if line.is_empty() { default } else { line }.to_string())
+}
+
+fn confirm(question: &str) -> Result<bool, String> {
+    let a = prompt(&format!("{question} (y/n)"), "n")?;
+    Ok(matches!(a.to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// reads a line without echoing it, so the key doesn't end up on screen
+fn prompt_secret(question: &str) -> Result<String, String> {
+    if !io::stdin().is_terminal() {
+        return prompt(question, "");
+    }
+    print!("{question}: ");
+    stdout().flush().map_err(|e| e.to_string())?;
+
+    enable_raw_mode().map_err(|e| e.to_string())?;
+    let mut s = String::new();
+    let result = loop {
+        match read() {
+            Ok(Event::Key(KeyEvent {
+                code,
+                modifiers,
+                kind: KeyEventKind::Press,
+                ..
+            })) => match code {
+                KeyCode::Enter => break Ok(()),
+                KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
+                    break Err("Setup aborted, nothing was written.".to_string());
+                }
+                KeyCode::Char(c) => s.push(c),
+                KeyCode::Backspace => {
+                    s.pop();
+                }
+                _ => {}
+            },
+            // pasted keys arrive as plain characters
+            Ok(_) => {}
+            Err(e) => break Err(e.to_string()),
+        }
+    };
+    disable_raw_mode().map_err(|e| e.to_string())?;
+    println!();
+    result.map(|_| s.trim().to_string())
+}
+
+/// the key goes in its own file next to the config, readable only by the user
+fn write_key_file(provider: Provider, key: &str) -> Result<PathBuf, String> {
+    let path = config_dir().join(format!("{}.key", provider.name()));
+    std::fs::create_dir_all(config_dir())
+        .and_then(|_| open_private(&path))
+        .and_then(|mut f| f.write_all(format!("{key}\n").as_bytes()))
+        .map_err(|e| format!("Could not write {}: {e}", path.display()))?;
+    Ok(path)
+}
+
+/// opens the file for writing without it ever being readable by
+/// others, also when it already existed with looser permissions
+#[cfg(unix)]
+fn open_private(path: &Path) -> io::Result<File> {
+    use std::{
+        fs::OpenOptions,
+        os::unix::fs::{OpenOptionsExt, PermissionsExt},
+    };
+
+    let f = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    f.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    Ok(f)
+}
+
+#[cfg(not(unix))]
+fn open_private(path: &Path) -> io::Result<File> {
+    File::create(path)
+}
+
+#[cfg(unix)]
+fn restrict_permissions(path: &Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+        .map_err(|e| format!("Could not set permissions on {}: {e}", path.display()))
+}
+
+#[cfg(not(unix))]
+fn restrict_permissions(_path: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+
+    #[test]
+    fn key_files_are_private() {
+        let path = std::env::temp_dir().join(format!("regn-test-{}.key", std::process::id()));
+        std::fs::write(&path, "old").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        open_private(&path).unwrap().write_all(b"new").unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(mode, 0o600);
+        assert_eq!(contents, "new");
+    }
+}
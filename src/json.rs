@@ -0,0 +1,166 @@
+use std::io;
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, Utc};
+use serde::Serialize;
+
+use crate::{
+    Regn,
+    api::{
+        api_main::parse_current_weather,
+        request::{RespForecastDay, RespHour},
+    },
+    arg::Command,
+    datetime::LocationZone,
+};
+
+/// bumped whenever a field is removed or changes meaning.
+/// adding fields does not change the version.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// the document written by --json. values are always metric, whatever
+/// --units says, and the unit is part of every field name.
+#[derive(Serialize)]
+pub struct JsonWeather {
+    pub schema_version: u32,
+    pub provider: &'static str,
+    pub location: JsonLocation,
+    pub current: JsonCurrent,
+    pub days: Vec<JsonDay>,
+    pub hours: Vec<JsonHour>,
+}
+
+#[derive(Serialize)]
+pub struct JsonLocation {
+    pub name: String,
+    pub country: String,
+    pub timezone: String,
+    /// RFC 3339 with the location's UTC offset
+    pub local_time: DateTime<FixedOffset>,
+}
+
+#[derive(Serialize)]
+pub struct JsonCurrent {
+    pub condition: String,
+    /// one of rain, snow, sun, cloud, clear, fog, thunder or unknown
+    pub condition_code: &'static str,
+    pub temp_c: f64,
+    pub feels_like_c: f64,
+    pub wind_kph: f64,
+    pub precip_mm: f64,
+    pub pressure_hpa: f64,
+    pub humidity_pct: f64,
+}
+
+#[derive(Serialize)]
+pub struct JsonDay {
+    pub date: NaiveDate,
+    pub condition: String,
+    pub condition_code: &'static str,
+    pub min_temp_c: f64,
+    pub max_temp_c: f64,
+    pub max_wind_kph: f64,
+    pub total_precip_mm: f64,
+    pub chance_of_rain_pct: i32,
+    pub sunrise: Option<DateTime<FixedOffset>>,
+    pub sunset: Option<DateTime<FixedOffset>>,
+}
+
+#[derive(Serialize)]
+pub struct JsonHour {
+    pub time: DateTime<FixedOffset>,
+    pub condition: String,
+    pub condition_code: &'static str,
+    pub temp_c: f64,
+    pub wind_kph: f64,
+    pub precip_mm: f64,
+    pub chance_of_rain_pct: i32,
+}
+
+impl Regn {
+    /// `--json` and `--json-pretty`
+    pub fn print_json(&self, pretty: bool) -> io::Result<()> {
+        let doc = self.json_weather();
+        let s = match pretty {
+            true => serde_json::to_string_pretty(&doc),
+            false => serde_json::to_string(&doc),
+        }
+        .map_err(io::Error::other)?;
+        println!("{s}");
+        Ok(())
+    }
+
+    pub fn json_weather(&self) -> JsonWeather {
+        let w = &self.weather;
+        let zone = &w.zone;
+
+        // `regn hourly` limits the hours like its text output does
+        let hours: Vec<JsonHour> = match self.args.command {
+            Command::Hourly => self.upcoming_hours().filter_map(|h| json_hour(h, zone)).collect(),
+            _ => w
+                .forecast_days
+                .iter()
+                .flat_map(|d| d.hour.iter())
+                .filter_map(|h| json_hour(h, zone))
+                .collect(),
+        };
+
+        JsonWeather {
+            schema_version: JSON_SCHEMA_VERSION,
+            provider: self.args.provider.name(),
+            location: JsonLocation {
+                name: w.location.name.clone(),
+                country: w.location.country.clone(),
+                timezone: zone.tz_id.clone(),
+                local_time: w.now.with_timezone(&zone.offset_at(w.now)),
+            },
+            current: JsonCurrent {
+                condition: w.current_condition_as_str.trim().to_string(),
+                condition_code: w.current_condition.name(),
+                temp_c: w.current_temp_c,
+                feels_like_c: w.current_feelslike_c,
+                wind_kph: w.current_wind_kph,
+                precip_mm: w.current_precip_mm,
+                pressure_hpa: w.current_pressure_mb,
+                humidity_pct: w.current_humidity,
+            },
+            days: w.forecast_days.iter().map(|d| json_day(d, zone)).collect(),
+            hours,
+        }
+    }
+}
+
+fn json_day(d: &RespForecastDay, zone: &LocationZone) -> JsonDay {
+    let at = |t: Option<NaiveTime>| {
+        t.map(|t| {
+            let instant = zone.to_utc(d.date.and_time(t));
+            instant.with_timezone(&zone.offset_at(instant))
+        })
+    };
+    let text = d.day.condition.text.trim();
+    JsonDay {
+        date: d.date,
+        condition: text.to_string(),
+        condition_code: parse_current_weather(text.to_string()).name(),
+        min_temp_c: d.day.mintemp_c,
+        max_temp_c: d.day.maxtemp_c,
+        max_wind_kph: d.day.maxwind_kph,
+        total_precip_mm: d.day.totalprecip_mm,
+        chance_of_rain_pct: d.day.daily_chance_of_rain,
+        sunrise: at(d.astro.sunrise),
+        sunset: at(d.astro.sunset),
+    }
+}
+
+fn json_hour(h: &RespHour, zone: &LocationZone) -> Option<JsonHour> {
+    let t: DateTime<Utc> = DateTime::from_timestamp(h.time_epoch, 0)?;
+    let text = h.condition.text.trim();
+    Some(JsonHour {
+        time: t.with_timezone(&zone.offset_at(t)),
+        condition: text.to_string(),
+        condition_code: parse_current_weather(text.to_string()).name(),
+        temp_c: h.temp_c,
+        wind_kph: h.wind_kph,
+        precip_mm: h.precip_mm,
+        chance_of_rain_pct: h.chance_of_rain,
+    })
+}
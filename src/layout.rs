@@ -0,0 +1,403 @@
+use std::io;
+
+use chrono::DateTime;
+use crossterm::{
+    QueueableCommand,
+    cursor::MoveTo,
+    style::{Color, Print, SetForegroundColor},
+};
+
+use crate::{Pos, Regn, WeatherFrame, api::request::RespForecastDay};
+
+/// lines in the hourly strip: time, temperature and chance of rain
+const HOURLY_LINES: u16 = 3;
+/// border and one column of padding on each side
+const PAD_W: u16 = 4;
+const PAD_H: u16 = 2;
+const MIN_DETAILS_WIDTH: u16 = 32;
+const MIN_DETAILS_LINES: u16 = 3;
+/// wider than this and the panels are centered instead
+const MAX_WIDTH: u16 = 140;
+
+/// the panels of the TUI, in focus order
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Panel {
+    Current,
+    Daily,
+    Hourly,
+    Details,
+}
+
+impl Panel {
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::Current => "Now",
+            Self::Daily => "Forecast",
+            Self::Hourly => "Hourly",
+            Self::Details => "Details",
+        }
+    }
+}
+
+/// part of the terminal, borders included
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct Rect {
+    pub col: u16,
+    pub row: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+/// where each panel goes. panels without room are left out.
+#[derive(PartialEq, Debug)]
+pub struct Layout {
+    pub panels: Vec<(Panel, Rect)>,
+    pub charts: Option<Rect>,
+}
+
+/// the size of the text each panel has to show, in columns and lines
+pub struct Content {
+    pub current: (u16, u16),
+    pub daily: (u16, u16),
+    pub charts: Option<(u16, u16)>,
+}
+
+/// side by side when there is room for the details next to the current
+/// conditions and the forecast, else stacked. None when not even the
+/// stacked panels fit.
+pub fn layout(cols: u16, rows: u16, c: &Content) -> Option<Layout> {
+    let width = cols.min(MAX_WIDTH);
+    let col = (cols - width) / 2;
+    let left_w = c.current.0.max(c.daily.0) + PAD_W;
+    let cur_h = c.current.1 + PAD_H;
+    let day_h = c.daily.1 + PAD_H;
+    let hour_h = HOURLY_LINES + PAD_H;
+    let charts_h = c
+        .charts
+        .filter(|(w, _)| w + PAD_W <= width)
+        .map_or(0, |(_, h)| h + PAD_H);
+    let rect = |row, width, height| Rect { col, row, width, height };
+
+    if width >= left_w + MIN_DETAILS_WIDTH && rows >= cur_h + day_h + hour_h {
+        let top_h = cur_h + day_h;
+        let charts_h = match rows >= top_h + hour_h + charts_h {
+            true => charts_h,
+            false => 0,
+        };
+        let row = (rows - top_h - hour_h - charts_h) / 2;
+        let details = Rect {
+            col: col + left_w,
+            row,
+            width: width - left_w,
+            height: top_h,
+        };
+        return Some(Layout {
+            panels: vec![
+                (Panel::Current, rect(row, left_w, cur_h)),
+                (Panel::Daily, rect(row + cur_h, left_w, day_h)),
+                (Panel::Hourly, rect(row + top_h, width, hour_h)),
+                (Panel::Details, details),
+            ],
+            charts: (charts_h > 0).then(|| rect(row + top_h + hour_h, width, charts_h)),
+        });
+    }
+
+    if width >= left_w && rows >= cur_h + day_h + hour_h {
+        let mut spare = rows - cur_h - day_h - hour_h;
+        let details_h = match spare >= MIN_DETAILS_LINES + PAD_H {
+            true => (MIN_DETAILS_LINES * 3 + PAD_H).min(spare),
+            false => 0,
+        };
+        spare -= details_h;
+        let charts_h = match spare >= charts_h {
+            true => charts_h,
+            false => 0,
+        };
+        spare -= charts_h;
+
+        let mut row = spare / 2;
+        let mut panels = Vec::new();
+        for (panel, height) in [
+            (Panel::Current, cur_h),
+            (Panel::Daily, day_h),
+            (Panel::Hourly, hour_h),
+            (Panel::Details, details_h),
+        ] {
+            if height > 0 {
+                panels.push((panel, rect(row, width, height)));
+                row += height;
+            }
+        }
+        return Some(Layout {
+            panels,
+            charts: (charts_h > 0).then(|| rect(row, width, charts_h)),
+        });
+    }
+    None
+}
+
+/// the size a list of lines needs
+fn measure(lines: &[String]) -> (u16, u16) {
+    let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    (width as u16, lines.len() as u16)
+}
+
+impl Regn {
+    /// draws the panels around the graph, if there is room for them
+    pub fn draw_panels(&mut self) -> io::Result<bool> {
+        let current = self.current_panel_lines();
+        let daily = self.daily_panel_lines();
+        let charts: Option<Vec<String>> = self
+            .args
+            .charts
+            .then(|| self.format_charts().into_iter().skip(1).collect());
+        let content = Content {
+            current: measure(&current),
+            daily: measure(&daily),
+            charts: charts.as_deref().map(measure),
+        };
+
+        let smallest = content.current.1 + content.daily.1 + HOURLY_LINES + PAD_H * 3;
+        let graph_height = self.graph_height(smallest);
+        let Some(layout) = layout(self.columns, self.view_rows() - graph_height, &content) else {
+            self.visible_panels.clear();
+            return Ok(false);
+        };
+        self.visible_panels = layout.panels.iter().map(|(p, _)| *p).collect();
+
+        for (panel, rect) in layout.panels.iter() {
+            let lines = match panel {
+                Panel::Current => current.clone(),
+                Panel::Daily => daily.clone(),
+                Panel::Hourly => self.hourly_strip((rect.width - PAD_W) as usize),
+                Panel::Details => self.details_panel_lines(),
+            };
+            let focused = self.focus == *panel;
+            self.w_panel(panel.title(), *rect, &lines, focused)?;
+        }
+        if let (Some(rect), Some(lines)) = (layout.charts, charts) {
+            self.w_panel("Charts", rect, &lines, false)?;
+        }
+        if graph_height > 0 {
+            self.temp_graph(graph_height)?;
+        }
+        Ok(true)
+    }
+
+    /// moves the focus to the next (or previous) panel on screen
+    pub fn move_focus(&mut self, forward: bool) -> io::Result<()> {
+        let shown = &self.visible_panels;
+        if shown.is_empty() {
+            return Ok(());
+        }
+        let at = shown.iter().position(|p| *p == self.focus);
+        let next = match (at, forward) {
+            (Some(i), true) => (i + 1) % shown.len(),
+            (Some(i), false) => (i + shown.len() - 1) % shown.len(),
+            (None, _) => 0,
+        };
+        self.focus = shown[next];
+        // the details follow whatever was focused before them
+        if self.focus != Panel::Details {
+            self.details_for = self.focus;
+        }
+        self.util_clear_screen()
+    }
+
+    /// a bordered panel with a title, its lines cut to fit
+    pub fn w_panel(&mut self, title: &str, rect: Rect, lines: &[String], focused: bool) -> io::Result<()> {
+        let mut f = WeatherFrame::new();
+        f.pos = Pos::new(rect.col, rect.row);
+        f.width = rect.width;
+        f.height = rect.height;
+        let color = match focused {
+            true => {
+                f.border = vec!['┏', '━', '┓', '┃', '┛', '┗'];
+                self.args.theme.focus_color()
+            }
+            false => self.args.theme.frame_color(),
+        };
+        self.sout.queue(SetForegroundColor(color))?;
+        self.w_rect(&f)?;
+        self.sout.queue(MoveTo(rect.col + 2, rect.row))?;
+        self.sout.queue(Print(format!(" {title} ")))?;
+        self.sout.queue(SetForegroundColor(self.args.theme.frame_color()))?;
+
+        let inner_w = rect.width.saturating_sub(PAD_W) as usize;
+        let inner_h = rect.height.saturating_sub(PAD_H) as usize;
+        // blank rows too, so nothing underneath shows through
+        for i in 0..inner_h {
+            let line: String = match lines.get(i) {
+                Some(l) => l.trim_start_matches('\n').chars().take(inner_w).collect(),
+                None => String::new(),
+            };
+            self.sout.queue(MoveTo(rect.col + 1, rect.row + 1 + i as u16))?;
+            self.sout.queue(Print(format!(" {line:<inner_w$} ")))?;
+        }
+        self.sout.queue(SetForegroundColor(Color::Reset))?;
+        Ok(())
+    }
+
+    fn current_panel_lines(&self) -> Vec<String> {
+        let mut s = self.format_header();
+        s.extend(self.format_current());
+        s
+    }
+
+    /// one line per day, without the heading. the day under the cursor
+    /// is marked while the panel has focus.
+    fn daily_panel_lines(&self) -> Vec<String> {
+        self.format_forecast()
+            .into_iter()
+            .skip(1)
+            .enumerate()
+            .map(|(i, line)| match self.focus == Panel::Daily && i == self.selected_day {
+                true => format!("▸ {line}"),
+                false => format!("  {line}"),
+            })
+            .collect()
+    }
+
+    /// the upcoming hours side by side, as many as fit in `width`
+    fn hourly_strip(&self, width: usize) -> Vec<String> {
+        let u = self.args.units;
+        let tf = &self.args.time_format;
+        let cells: Vec<[String; 3]> = self
+            .upcoming_hours()
+            .filter_map(|h| {
+                let t = DateTime::from_timestamp(h.time_epoch, 0)?;
+                let (wall, _) = self.args.time_zone.wall_clock(t, &self.weather.zone);
+                Some([tf.time(wall.time()), u.temp(h.temp_c), format!("{}%", h.chance_of_rain)])
+            })
+            .collect();
+        let cell_w = cells
+            .iter()
+            .flat_map(|c| c.iter().map(|s| s.chars().count()))
+            .max()
+            .unwrap_or(0)
+            + 2;
+        let fit = (width / cell_w).max(1);
+        (0..HOURLY_LINES as usize)
+            .map(|line| {
+                cells
+                    .iter()
+                    .take(fit)
+                    .map(|c| format!("{:<cell_w$}", c[line]))
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    /// more about the focused panel
+    fn details_panel_lines(&self) -> Vec<String> {
+        match self.details_for {
+            Panel::Daily => match self.selected_forecast_day() {
+                Some(day) => self.format_day_details(day),
+                None => Vec::new(),
+            },
+            Panel::Hourly => self.format_hourly(),
+            _ => self.current_details(),
+        }
+    }
+
+    fn current_details(&self) -> Vec<String> {
+        let u = self.args.units;
+        let w = &self.weather;
+        let (updated, _) = self.args.time_zone.wall_clock(w.fetched_at, &w.zone);
+        let mut s = vec![
+            format!("Feels like {}", u.temp(w.current_feelslike_c)),
+            format!("Humidity {}%", w.current_humidity),
+            format!("Wind {}", u.wind(w.current_wind_kph)),
+            format!("Pressure {}", u.pressure(w.current_pressure_mb)),
+            format!("Precip. {}", u.precip(w.current_precip_mm)),
+        ];
+        if let Some(today) = w.forecast_days.first() {
+            s.push(self.format_astro(&today.astro, today.date));
+        }
+        s.push(format!("Updated {}", self.args.time_format.time(updated.time())));
+        s
+    }
+
+    /// everything known about one forecast day
+    pub fn format_day_details(&self, day: &RespForecastDay) -> Vec<String> {
+        let u = self.args.units;
+        let d = &day.day;
+        let today = self.weather.location.localtime.date();
+        let label = self.args.time_format.day_label(day.date, today);
+        let date = self.args.time_format.date(day.date);
+        vec![
+            match label == date {
+                true => label,
+                false => format!("{label} ({date})"),
+            },
+            d.condition.text.trim().to_string(),
+            format!("High {}, low {}", u.temp(d.maxtemp_c), u.temp(d.mintemp_c)),
+            format!(
+                "Chance of rain {}%, precip. {}",
+                d.daily_chance_of_rain,
+                u.precip(d.totalprecip_mm)
+            ),
+            format!("Wind up to {}", u.wind(d.maxwind_kph)),
+            self.format_astro(&day.astro, day.date),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENT: Content = Content {
+        current: (30, 4),
+        daily: (30, 7),
+        charts: Some((60, 8)),
+    };
+
+    fn panels(l: &Layout) -> Vec<Panel> {
+        l.panels.iter().map(|(p, _)| *p).collect()
+    }
+
+    #[test]
+    fn side_by_side_or_stacked() {
+        // 34 columns for the left panels and 32 for the details
+        let l = layout(66, 20, &CONTENT).unwrap();
+        assert_eq!(panels(&l), [Panel::Current, Panel::Daily, Panel::Hourly, Panel::Details]);
+        assert_eq!(l.panels[3].1, Rect { col: 34, row: 0, width: 32, height: 15 });
+        assert_eq!(l.charts, None);
+
+        // one column less and the details only fit below, which they
+        // don't here
+        let l = layout(65, 20, &CONTENT).unwrap();
+        assert_eq!(panels(&l), [Panel::Current, Panel::Daily, Panel::Hourly]);
+
+        let l = layout(60, 40, &CONTENT).unwrap();
+        assert_eq!(l.panels[3], (Panel::Details, Rect { col: 0, row: 24, width: 60, height: 11 }));
+        assert_eq!(l.charts, None);
+
+        assert_eq!(layout(33, 40, &CONTENT), None);
+        assert_eq!(layout(80, 19, &CONTENT), None);
+    }
+
+    #[test]
+    fn wide_screens_are_centered() {
+        let l = layout(200, 40, &CONTENT).unwrap();
+        assert_eq!(l.panels[0].1, Rect { col: 30, row: 5, width: 34, height: 6 });
+        assert_eq!(l.charts, Some(Rect { col: 30, row: 25, width: 140, height: 10 }));
+    }
+
+    #[test]
+    fn panels_stay_on_screen() {
+        for cols in 0..=160 {
+            for rows in 0..=50 {
+                let Some(l) = layout(cols, rows, &CONTENT) else {
+                    continue;
+                };
+                for r in l.panels.iter().map(|(_, r)| r).chain(&l.charts) {
+                    assert!(r.col + r.width <= cols && r.row + r.height <= rows, "{cols}x{rows}: {r:?}");
+                }
+            }
+        }
+    }
+}
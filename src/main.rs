@@ -13,43 +13,127 @@ use rand::{RngExt, rng};
 
 mod api;
 mod arg;
+mod bar;
+mod chart;
+mod commands;
+mod completions;
+mod config;
 mod controls;
+mod csv;
+mod datetime;
+mod day;
+mod export;
+mod graph;
 mod help;
+mod history;
+mod icons;
+mod ics;
+mod init;
+mod json;
+mod layout;
+mod output;
+mod places;
+mod prompt;
+mod report;
+mod serve;
+mod status;
+mod template;
+mod theme;
+mod units;
 mod utils;
 
 use crate::{
     api::api_main::{CurrentCondition, WeatherAPI},
-    arg::{Arguments, parse_args},
-    utils::get_fps,
+    arg::{Arguments, Command, parse_args},
+    help::{APP_NAME, APP_VERS, print_help},
+    layout::Panel,
+    output::OutputFormat,
+    prompt::Prompt,
+    utils::{exit_with_error, get_fps},
 };
 
-const FPS: f64 = 60.0;
 const RAIN_ANIM_FPS_DIV: i32 = 4;
 const CLEAR_ANIM_FPS_DIV: i32 = 13;
 const SNOW_ANIM_FPS_DIV: i32 = 13;
 
 fn main() -> io::Result<()> {
     // get commandline argument launch
-    let args: Arguments = parse_args();
+    let args: Arguments = match parse_args(std::env::args().skip(1)) {
+        Ok(a) => a,
+        Err(e) => exit_with_error(&e, e.exit_code()),
+    };
+
+    // if help
+    if args.help {
+        print_help(args.help_topic);
+        return Ok(());
+    }
+
+    // if --version
+    if args.version {
+        println!("{APP_NAME} {APP_VERS}");
+        return Ok(());
+    }
+
+    // commands that don't need weather data
+    let done = match &args.command {
+        Command::Init => Some(init::run_init()),
+        Command::Export => Some(export::run_export(&args)),
+        Command::Ics => Some(ics::run_ics(&args)),
+        Command::Completions(shell) => Some(completions::print_completions(*shell)),
+        Command::CompleteLocations => Some(completions::print_locations(&args.places)),
+        Command::Man => Some(help::print_man_page()),
+        Command::Places(cmd) => Some(places::run_places_cmd(cmd, &args.places)),
+        Command::Search(query) => Some(commands::run_search(&args, query)),
+        Command::Config(cmd) => Some(commands::run_config(&args, *cmd)),
+        _ => None,
+    };
+    if let Some(result) = done {
+        if let Err(e) = result {
+            exit_with_error(&e, 1);
+        }
+        return Ok(());
+    }
 
     // fetch weather data from API
-    let weather: WeatherAPI = api::api_main::api_main(&args.location, &args.forecast);
+    let weather: WeatherAPI = match api::api_main::api_main(
+        &args.provider,
+        &args.key_source,
+        &args.location,
+        &args.forecast,
+        args.max_age,
+    ) {
+        Ok(w) => w,
+        Err(e) => exit_with_error(&e, 1),
+    };
+    if let Some(loc) = &args.typed_location {
+        history::add_to_history(loc);
+    }
 
     let sout = stdout();
     let mut r = Regn::new(sout, weather, args);
 
+    if r.args.command == Command::Report {
+        return r.print_report();
+    }
+
+    if r.args.output != OutputFormat::Text {
+        return r.print_formatted();
+    }
+
+    match r.args.command {
+        Command::Now => return r.print_now(),
+        Command::Forecast => return r.print_forecast(),
+        Command::Hourly => return r.print_hourly(),
+        _ => {}
+    }
+
     // if -t
     if r.args.no_tui {
         r.f_stdout_direct()?;
         return Ok(());
     }
 
-    // if help
-    if r.args.help {
-        r.print_help();
-        return Ok(());
-    }
-
     r.util_setup()?;
 
     while r.prog_state != ProgState::Quit {
@@ -86,19 +170,27 @@ impl WeatherFrame {
     fn find_optimal_width_and_height(&mut self) {
         let account_for_borders_w = 8;
         let account_for_borders_h = 4;
-        let mut lines_sort_by_len = self.lines.clone();
-
-        lines_sort_by_len.sort_by_key(|s| s.len());
-        let longest = lines_sort_by_len.last().unwrap().chars().count();
+        let longest = self.lines.iter().map(|s| s.chars().count()).max().unwrap_or(0);
 
         self.width = longest as u16 + account_for_borders_w;
         self.height = self.lines.len() as u16 + account_for_borders_h;
     }
 
+    /// a frame bigger than the viewport is put in its top left corner
     fn make_centered(&mut self, vp_cols: u16, vp_rows: u16) {
-        let center_of_vp: (u16, u16) = (vp_cols / 2, vp_rows / 2);
-        self.pos.col = center_of_vp.0 - (self.width / 2);
-        self.pos.row = center_of_vp.1 - (self.height / 2);
+        self.pos.col = vp_cols.saturating_sub(self.width) / 2;
+        self.pos.row = vp_rows.saturating_sub(self.height) / 2;
+    }
+
+    fn fits(&self, vp_cols: u16, vp_rows: u16) -> bool {
+        self.width <= vp_cols && self.height <= vp_rows
+    }
+
+    fn from_lines(lines: Vec<String>) -> Self {
+        let mut f = Self::new();
+        f.lines = lines;
+        f.find_optimal_width_and_height();
+        f
     }
 }
 
@@ -125,6 +217,12 @@ struct Precipitation {
 #[derive(PartialEq)]
 enum ProgState {
     Main,
+    /// the detail view of the selected forecast day
+    Day,
+    /// the location prompt, opened with /
+    Search,
+    /// the keys, opened with ?
+    Help,
     Quit,
 }
 
@@ -137,6 +235,17 @@ struct Regn {
     fps: Duration,
     args: Arguments,
     anim_frame_counter: i32,
+    /// the temperature graph, toggled with g
+    show_graph: bool,
+    /// the panel that has keyboard focus
+    focus: Panel,
+    /// the panel the details pane is about
+    details_for: Panel,
+    /// the panels that fit on screen, in focus order
+    visible_panels: Vec<Panel>,
+    /// the forecast day under the cursor
+    selected_day: usize,
+    prompt: Prompt,
     // rainy, snowy and clear weather
     precipitation: Vec<Precipitation>,
 }
@@ -148,31 +257,28 @@ impl Regn {
             columns: 0,
             rows: 0,
             weather,
+            fps: get_fps(args.fps),
+            show_graph: args.graph,
+            focus: Panel::Current,
+            details_for: Panel::Current,
+            visible_panels: Vec::new(),
+            selected_day: 0,
+            prompt: Prompt::new(),
             args,
             prog_state: ProgState::Main,
-            fps: get_fps(FPS),
             anim_frame_counter: 0,
             // rain_animation
             precipitation: Vec::new(),
         }
     }
 
-    fn f_stdout_direct(&mut self) -> io::Result<()> {
-        for line in self.format_weather_data() {
-            println!("{}", line);
-        }
-        Ok(())
-    }
-
     fn snow_animation(&mut self) -> io::Result<()> {
         let droplets_to_gen_each_frame: i32 = self.columns as i32 / 10;
         let max_amt_of_droplets: usize = self.columns as usize;
         let mut rng = rng();
 
         let drop_chars: Vec<&str> = vec!["*", "o", "."];
-
-        let drop_colors: Vec<Color> =
-        vec![Color::DarkGrey, Color::Grey, Color::Reset, Color::White];
+        let drop_colors: Vec<Color> = self.args.theme.particle_colors();
 
         // generate droplets
         if self.precipitation.len() < max_amt_of_droplets {
@@ -227,8 +333,7 @@ impl Regn {
         let max_amt_of_droplets: usize = self.columns as usize / 3;
 
         let drop_chars: Vec<&str> = vec![",", ".", "*"];
-        let drop_colors: Vec<Color> =
-        vec![Color::DarkGrey, Color::Grey, Color::Reset, Color::White];
+        let drop_colors: Vec<Color> = self.args.theme.particle_colors();
 
         let mut rng = rng();
 
@@ -286,8 +391,7 @@ impl Regn {
         let max_amt_of_droplets: usize = self.columns as usize;
 
         let drop_chars: Vec<&str> = vec!["/", "."];
-        let drop_colors: Vec<Color> =
-        vec![Color::DarkGrey, Color::Grey, Color::Reset, Color::White];
+        let drop_colors: Vec<Color> = self.args.theme.particle_colors();
 
         let mut rng = rng();
 
@@ -350,52 +454,59 @@ impl Regn {
         Ok(())
     }
 
-    fn format_weather_data(&mut self) -> Vec<String> {
-        let mut s = Vec::new();
-
-        s.push(format!("{time}", time = self.weather.location.localtime));
-
-        s.push(format!(
-            "{city}, {country}",
-            city = self.weather.location.name,
-            country = self.weather.location.country,
-        ));
-
-        s.push(format!(
-            "{temp}°C, {cond}",
-            temp = self.weather.current_temp_c,
-            cond = self.weather.current_condition_as_str
-        ));
-
-        s.push(format!(
-            "\n{}-Day Forecast:",
-            self.weather.forecast_days.len()
-        ));
-
-        for day in self.weather.forecast_days.iter() {
-            s.push(format!(
-                "{}: {}°C / {}°C ({})",
-                day.date,
-                day.day.maxtemp_c,
-                day.day.mintemp_c,
-                day.day.condition.text.trim()
-            ));
+    fn weather_frame(&mut self) -> io::Result<()> {
+        if self.draw_panels()? {
+            return Ok(());
         }
-        s
-    }
 
-    fn weather_frame(&mut self) -> io::Result<()> {
-        let mut f = WeatherFrame::new();
-        for line in self.format_weather_data() {
-            f.lines.push(line);
+        // without room for the panels, show the current conditions only
+        let mut f = WeatherFrame::from_lines(self.format_compact());
+        if !f.fits(self.columns, self.view_rows()) {
+            return self.too_small();
         }
-        f.find_optimal_width_and_height();
-        f.make_centered(self.columns, self.rows);
+        f.make_centered(self.columns, self.view_rows());
+
+        self.sout
+            .queue(SetForegroundColor(self.args.theme.frame_color()))?;
         self.w_rect(&f)?;
         self.w_text(f)?;
+        self.sout.queue(SetForegroundColor(Color::Reset))?;
+        Ok(())
+    }
+
+    /// location and current conditions, for small terminals
+    fn format_compact(&self) -> Vec<String> {
+        let mut s = vec![format!(
+            "{}, {}",
+            self.weather.location.name, self.weather.location.country
+        )];
+        s.extend(self.format_current());
+        s
+    }
+
+    /// shown instead of the frame when not even the compact one fits
+    fn too_small(&mut self) -> io::Result<()> {
+        let msg: String = "Terminal too small"
+            .chars()
+            .take(self.columns as usize)
+            .collect();
+        let col = self.columns.saturating_sub(msg.chars().count() as u16) / 2;
+        self.sout.queue(MoveTo(col, self.view_rows() / 2))?;
+        self.sout.queue(SetForegroundColor(self.args.theme.frame_color()))?;
+        self.sout.queue(Print(msg))?;
+        self.sout.queue(SetForegroundColor(Color::Reset))?;
         Ok(())
     }
 
+    /// the terminal changed size: start the animation over and draw
+    /// everything again at the new size
+    fn resize(&mut self, columns: u16, rows: u16) -> io::Result<()> {
+        self.columns = columns;
+        self.rows = rows;
+        self.precipitation.clear();
+        self.util_clear_screen()
+    }
+
     fn w_text(&mut self, f: WeatherFrame) -> io::Result<()> {
         let init_pos: Pos = Pos {
             col: f.pos.col + 4,
@@ -404,7 +515,7 @@ impl Regn {
         for (i, line) in f.lines.iter().enumerate() {
             self.sout
                 .queue(MoveTo(init_pos.col, init_pos.row + i as u16))?;
-            self.sout.write(line.as_bytes())?;
+            self.sout.write_all(line.as_bytes())?;
         }
 
         Ok(())
@@ -418,8 +529,8 @@ impl Regn {
 
         let x0 = r.pos.col;
         let y0 = r.pos.row;
-        let w = r.width as u16;
-        let h = r.height as u16;
+        let w = r.width;
+        let h = r.height;
 
         // 1x1: just a corner char (pick top-left)
         if w == 1 && h == 1 {
@@ -472,8 +583,31 @@ impl Regn {
     }
 
     fn main_loop(&mut self) -> io::Result<()> {
+        // particles are placed at random columns, which needs at least one
+        if self.args.animations && self.columns > 0 && self.rows > 0 {
+            self.weather_animation()?;
+        }
+        match self.prog_state {
+            ProgState::Day => self.day_view()?,
+            ProgState::Search => {
+                self.weather_frame()?;
+                self.prompt_search()?;
+                self.draw_prompt()?;
+            }
+            ProgState::Help => {
+                self.weather_frame()?;
+                self.draw_help()?;
+            }
+            _ => self.weather_frame()?,
+        }
+        self.status_line()?;
+
+        Ok(())
+    }
+
+    fn weather_animation(&mut self) -> io::Result<()> {
         // weather animation
-        match self.weather.current_condition {
+        match self.shown_condition() {
             CurrentCondition::Rain => {
                 if self.anim_frame_counter >= RAIN_ANIM_FPS_DIV {
                     self.anim_frame_counter = 0;
@@ -518,8 +652,6 @@ impl Regn {
                 }
             }
         }
-        self.weather_frame()?;
-
         Ok(())
     }
 }
@@ -0,0 +1,336 @@
+use std::io::{self, IsTerminal};
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
+use crossterm::style::Stylize;
+
+use crate::{
+    Regn,
+    api::{
+        api_main::parse_current_weather,
+        request::{RespAstro, RespHour},
+    },
+    arg::Command,
+    bar::Bar,
+    datetime::{DisplayZone, fmt_utc_offset},
+    icons::condition_icon,
+    template::Template,
+    theme::{condition_rgb, temp_rgb, term_color},
+};
+
+/// how results are written when not showing the TUI
+#[derive(PartialEq, Clone, Debug)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    JsonPretty,
+    Csv,
+    Tsv,
+    Template(Template),
+    /// a status bar, with --format as its text if given
+    Bar(Bar, Option<Template>),
+}
+
+/// when to colour the text output
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "auto" => Some(Self::Auto),
+            "always" => Some(Self::Always),
+            "never" => Some(Self::Never),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Always => "always",
+            Self::Never => "never",
+        }
+    }
+}
+
+impl Regn {
+    pub fn f_stdout_direct(&mut self) -> io::Result<()> {
+        let mut lines = self.format_weather_data();
+        if self.args.charts {
+            lines.extend(self.format_charts());
+        }
+        for line in lines {
+            println!("{}", line);
+        }
+        Ok(())
+    }
+
+    /// `regn now`
+    pub fn print_now(&mut self) -> io::Result<()> {
+        for line in self.format_header().into_iter().chain(self.format_current()) {
+            println!("{}", line);
+        }
+        Ok(())
+    }
+
+    /// `regn forecast`
+    pub fn print_forecast(&mut self) -> io::Result<()> {
+        for line in self.format_header().into_iter().chain(self.format_forecast()) {
+            println!("{}", line);
+        }
+        Ok(())
+    }
+
+    /// `regn hourly`
+    pub fn print_hourly(&mut self) -> io::Result<()> {
+        for line in self.format_header().into_iter().chain(self.format_hourly()) {
+            println!("{}", line);
+        }
+        Ok(())
+    }
+
+    /// output for anything other than plain text
+    pub fn print_formatted(&mut self) -> io::Result<()> {
+        match &self.args.output {
+            OutputFormat::Text => self.f_stdout_direct(),
+            OutputFormat::Json => self.print_json(false),
+            OutputFormat::JsonPretty => self.print_json(true),
+            OutputFormat::Csv => self.print_table(','),
+            OutputFormat::Tsv => self.print_table('\t'),
+            OutputFormat::Bar(bar, text) => self.print_bar(*bar, text.as_ref()),
+            OutputFormat::Template(t) => {
+                println!("{}", self.render_template(t));
+                Ok(())
+            }
+        }
+    }
+
+    /// icons and colours are for people reading the text output in a
+    /// terminal, not for pipes, bars or the TUI frame
+    fn rich_text(&self) -> bool {
+        let text_mode = self.args.no_tui
+            || matches!(
+                self.args.command,
+                Command::Now | Command::Forecast | Command::Hourly
+            );
+        text_mode
+            && self.args.output == OutputFormat::Text
+            && (self.args.color == ColorChoice::Always || io::stdout().is_terminal())
+    }
+
+    fn use_color(&self) -> bool {
+        self.rich_text()
+            && match self.args.color {
+                ColorChoice::Always => true,
+                ColorChoice::Never => false,
+                ColorChoice::Auto => std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()),
+            }
+    }
+
+    pub fn paint(&self, s: String, rgb: (u8, u8, u8)) -> String {
+        match self.use_color() {
+            true => s.with(term_color(rgb)).to_string(),
+            false => s,
+        }
+    }
+
+    /// a temperature in the chosen unit, coloured by how warm it is
+    fn temp_text(&self, celsius: f64) -> String {
+        self.paint(self.args.units.temp(celsius), temp_rgb(celsius))
+    }
+
+    /// a condition description, coloured by the kind of weather
+    fn condition_text(&self, text: &str) -> String {
+        let c = parse_current_weather(text.to_string());
+        self.paint(text.trim().to_string(), condition_rgb(c))
+    }
+
+    /// the hours shown by `regn hourly`: the next `--hours` hours from
+    /// now, counting the one in progress
+    pub fn upcoming_hours(&self) -> impl Iterator<Item = &RespHour> {
+        let from = self.weather.now.timestamp() - 3600;
+        self.weather
+            .forecast_days
+            .iter()
+            .flat_map(|d| d.hour.iter())
+            .filter(move |h| h.time_epoch > from)
+            .take(self.args.hours as usize)
+    }
+
+    pub fn format_weather_data(&self) -> Vec<String> {
+        let mut s = self.format_header();
+        s.extend(self.format_current());
+        s.extend(self.format_forecast());
+        s
+    }
+
+    /// local times and the location name
+    pub fn format_header(&self) -> Vec<String> {
+        let mut s = Vec::new();
+
+        let tf = &self.args.time_format;
+        let w = &self.weather;
+
+        // remote local time, and ours if it differs
+        let (remote, remote_off) =
+            DisplayZone::Location.wall_clock(w.now, &w.zone);
+        s.push(format!(
+            "{time} ({off}, {tz})",
+            time = tf.date_time(remote),
+            off = fmt_utc_offset(remote_off),
+            tz = w.zone.tz_id
+        ));
+        let (local, local_off) = DisplayZone::Local.wall_clock(w.now, &w.zone);
+        if local_off != remote_off {
+            s.push(format!(
+                "Your time: {time} ({off})",
+                time = tf.date_time(local),
+                off = fmt_utc_offset(local_off)
+            ));
+        }
+
+        s.push(format!(
+            "{city}, {country}",
+            city = self.weather.location.name,
+            country = self.weather.location.country,
+        ));
+        s
+    }
+
+    pub fn format_current(&self) -> Vec<String> {
+        let mut s = Vec::new();
+        let u = self.args.units;
+
+        s.push(format!(
+            "{temp}, {cond}",
+            temp = self.temp_text(self.weather.current_temp_c),
+            cond = self.condition_text(&self.weather.current_condition_as_str)
+        ));
+
+        s.push(format!(
+            "Feels like {feels}, wind {wind}",
+            feels = self.temp_text(self.weather.current_feelslike_c),
+            wind = u.wind(self.weather.current_wind_kph),
+        ));
+
+        s.push(format!(
+            "Precip. {precip}, pressure {pressure}",
+            precip = u.precip(self.weather.current_precip_mm),
+            pressure = u.pressure(self.weather.current_pressure_mb),
+        ));
+
+        if let Some(today) = self.weather.forecast_days.first() {
+            s.push(self.format_astro(&today.astro, today.date));
+        }
+
+        if self.rich_text() {
+            let rgb = condition_rgb(self.weather.current_condition);
+            let icon = condition_icon(self.weather.current_condition);
+            let mut text = s.into_iter();
+            s = icon
+                .iter()
+                .map(|line| {
+                    let icon_line = self.paint(line.to_string(), rgb);
+                    format!("{icon_line} {}", text.next().unwrap_or_default())
+                })
+                .collect();
+            s.extend(text);
+        }
+        s
+    }
+
+    pub fn format_forecast(&self) -> Vec<String> {
+        let mut s = Vec::new();
+        let tf = &self.args.time_format;
+        let u = self.args.units;
+        let today = self.weather.location.localtime.date();
+
+        s.push(format!(
+            "\n{}-Day Forecast:",
+            self.weather.forecast_days.len()
+        ));
+
+        for day in self.weather.forecast_days.iter() {
+            s.push(format!(
+                "{}: {} / {}, {}, {} ({})",
+                tf.day_label(day.date, today),
+                self.temp_text(day.day.maxtemp_c),
+                self.temp_text(day.day.mintemp_c),
+                u.wind(day.day.maxwind_kph),
+                u.precip(day.day.totalprecip_mm),
+                self.condition_text(&day.day.condition.text)
+            ));
+        }
+        s
+    }
+
+    /// the next `--hours` hours from now, in the chosen display timezone,
+    /// with a date line whenever the day changes
+    pub fn format_hourly(&self) -> Vec<String> {
+        let mut s = Vec::new();
+        let tf = &self.args.time_format;
+        let u = self.args.units;
+        let zone = &self.weather.zone;
+
+        let mut last_date: Option<NaiveDate> = None;
+        for h in self.upcoming_hours() {
+            let Some(t) = DateTime::from_timestamp(h.time_epoch, 0) else {
+                continue;
+            };
+            let (wall, off) = self.args.time_zone.wall_clock(t, zone);
+            if last_date != Some(wall.date()) {
+                last_date = Some(wall.date());
+                s.push(format!(
+                    "\n{} ({}):",
+                    tf.date(wall.date()),
+                    fmt_utc_offset(off)
+                ));
+            }
+            s.push(format!(
+                "{}: {}, {}% rain, {}, {} ({})",
+                tf.time(wall.time()),
+                self.temp_text(h.temp_c),
+                h.chance_of_rain,
+                u.wind(h.wind_kph),
+                u.precip(h.precip_mm),
+                self.condition_text(&h.condition.text)
+            ));
+        }
+        s
+    }
+
+    /// a wall-clock time at the location (like sunrise) in the chosen
+    /// display timezone
+    pub fn display_time(&self, date: NaiveDate, t: NaiveTime) -> (NaiveDateTime, FixedOffset) {
+        let zone = &self.weather.zone;
+        let instant = zone.to_utc(date.and_time(t));
+        self.args.time_zone.wall_clock(instant, zone)
+    }
+
+    /// sunrise and sunset in the chosen display timezone
+    pub fn format_astro(&self, astro: &RespAstro, date: NaiveDate) -> String {
+        let tf = &self.args.time_format;
+        let mut offset = None;
+        let mut render = |t: Option<NaiveTime>| match t {
+            Some(t) => {
+                let (wall, off) = self.display_time(date, t);
+                offset = Some(off);
+                tf.time(wall.time())
+            }
+            None => "none".to_string(),
+        };
+        let rise = render(astro.sunrise);
+        let set = render(astro.sunset);
+        match offset {
+            Some(off) => format!(
+                "Sunrise {rise}, sunset {set} ({})",
+                fmt_utc_offset(off)
+            ),
+            None => format!("Sunrise {rise}, sunset {set}"),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+
+use toml_edit::{Item, Table, value};
+
+use crate::config::{config_path, edit_config};
+
+/// `regn places ...`
+#[derive(PartialEq, Clone, Debug)]
+pub enum PlacesCmd {
+    Add { name: String, location: String },
+    Remove { name: String },
+    List,
+}
+
+impl PlacesCmd {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let usage = "Usage: regn places add <name> <location> | remove <name> | list";
+        match args.next().as_deref() {
+            Some("add") => match (args.next(), args.next()) {
+                (Some(name), Some(location)) => Ok(Self::Add {
+                    name: place_name(&name)?,
+                    location,
+                }),
+                _ => Err(usage.to_string()),
+            },
+            Some("remove") | Some("rm") => match args.next() {
+                Some(name) => Ok(Self::Remove {
+                    name: place_name(&name)?,
+                }),
+                None => Err(usage.to_string()),
+            },
+            Some("list") | Some("ls") | None => Ok(Self::List),
+            Some(other) => Err(format!("Unknown places command \"{other}\". {usage}")),
+        }
+    }
+}
+
+/// names are given with or without the leading "@"
+fn place_name(name: &str) -> Result<String, String> {
+    let n = name.trim_start_matches('@');
+    if n.is_empty() || n.chars().any(|c| c.is_whitespace()) {
+        return Err(format!("\"{name}\" is not a valid place name."));
+    }
+    Ok(n.to_string())
+}
+
+/// "@cabin" is looked up among the saved places, anything else
+/// is passed on to the provider as is
+pub fn resolve_location(
+    location: String,
+    places: &BTreeMap<String, String>,
+) -> Result<String, String> {
+    let Some(name) = location.strip_prefix('@') else {
+        return Ok(location);
+    };
+    match places.get(name) {
+        Some(loc) => Ok(loc.clone()),
+        None if places.is_empty() => Err(format!(
+            "There is no saved place named \"@{name}\". Add one with: regn places add {name} <location>"
+        )),
+        None => Err(format!(
+            "There is no saved place named \"@{name}\". Saved places: {}",
+            places
+                .keys()
+                .map(|k| format!("@{k}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+pub fn run_places_cmd(
+    cmd: &PlacesCmd,
+    places: &BTreeMap<String, String>,
+) -> Result<(), String> {
+    match cmd {
+        PlacesCmd::List => {
+            if places.is_empty() {
+                println!("No saved places. Add one with: regn places add <name> <location>");
+            }
+            let width = places.keys().map(|k| k.chars().count()).max().unwrap_or(0);
+            for (name, loc) in places {
+                println!("@{name:<width$}  {loc}");
+            }
+            Ok(())
+        }
+        PlacesCmd::Add { name, location } => {
+            edit_places(|t| {
+                t.insert(name, value(location.as_str()));
+                Ok(())
+            })?;
+            println!("Saved @{name} = \"{location}\"");
+            Ok(())
+        }
+        PlacesCmd::Remove { name } => {
+            edit_places(|t| match t.remove(name) {
+                Some(_) => Ok(()),
+                None => Err(format!("There is no saved place named \"@{name}\".")),
+            })?;
+            println!("Removed @{name}");
+            Ok(())
+        }
+    }
+}
+
+/// edits the [places] table in place, keeping the rest of the
+/// config file (comments included) untouched
+fn edit_places(
+    f: impl FnOnce(&mut Table) -> Result<(), String>,
+) -> Result<(), String> {
+    edit_config(|doc| {
+        let places = doc
+            .entry("places")
+            .or_insert(Item::Table(Table::new()))
+            .as_table_mut()
+            .ok_or(format!("\"places\" in {} is not a table.", config_path().display()))?;
+        f(places)
+    })
+}
@@ -0,0 +1,283 @@
+use std::{
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    ProgState, Regn,
+    api::api_main::{api_main, search_main},
+    history::{add_to_history, load_history},
+    layout::Rect,
+    places::resolve_location,
+};
+
+/// the provider is asked once typing pauses for this long
+const SEARCH_DELAY: Duration = Duration::from_millis(400);
+/// shorter queries only match saved places and the history
+const MIN_SEARCH_LEN: usize = 3;
+const MAX_SUGGESTIONS: usize = 8;
+const MAX_PROMPT_WIDTH: u16 = 64;
+
+/// where a suggestion came from
+#[derive(PartialEq, Clone, Copy)]
+pub enum Source {
+    Place,
+    History,
+    Provider,
+}
+
+impl Source {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Place => "place",
+            Self::History => "recent",
+            Self::Provider => "search",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Suggestion {
+    /// what is shown, and what Tab completes the query to
+    pub label: String,
+    /// what is passed to the provider
+    pub location: String,
+    pub source: Source,
+}
+
+/// the `/` location prompt
+pub struct Prompt {
+    pub query: String,
+    /// the selected suggestion, if any
+    pub selected: Option<usize>,
+    pub suggestions: Vec<Suggestion>,
+    /// matches from the provider for `searched`
+    found: Vec<Suggestion>,
+    searched: String,
+    last_edit: Instant,
+    /// an error, or what is being loaded
+    pub message: Option<String>,
+}
+
+impl Prompt {
+    pub fn new() -> Self {
+        Self {
+            query: String::new(),
+            selected: None,
+            suggestions: Vec::new(),
+            found: Vec::new(),
+            searched: String::new(),
+            last_edit: Instant::now(),
+            message: None,
+        }
+    }
+}
+
+impl Regn {
+    pub fn open_prompt(&mut self) -> io::Result<()> {
+        self.prompt = Prompt::new();
+        self.update_suggestions();
+        self.prog_state = ProgState::Search;
+        self.util_clear_screen()
+    }
+
+    pub fn close_prompt(&mut self) -> io::Result<()> {
+        self.prog_state = ProgState::Main;
+        self.util_clear_screen()
+    }
+
+    /// the query changed: start over with the suggestions
+    pub fn prompt_edit(&mut self, c: Option<char>) -> io::Result<()> {
+        match c {
+            Some(c) => self.prompt.query.push(c),
+            None => {
+                self.prompt.query.pop();
+            }
+        }
+        self.prompt.last_edit = Instant::now();
+        self.prompt.selected = None;
+        self.prompt.message = None;
+        self.update_suggestions();
+        // the box may have shrunk
+        self.util_clear_screen()
+    }
+
+    /// moves the selection over the suggestions. above the first one
+    /// is the query itself.
+    pub fn prompt_select(&mut self, down: bool) {
+        let n = self.prompt.suggestions.len();
+        self.prompt.selected = match (self.prompt.selected, down) {
+            (None, true) if n > 0 => Some(0),
+            (Some(i), true) => Some((i + 1).min(n.saturating_sub(1))),
+            (Some(0), false) => None,
+            (Some(i), false) => Some(i - 1),
+            (s, _) => s,
+        };
+    }
+
+    /// completes the query to the selected (or first) suggestion
+    pub fn prompt_complete(&mut self) {
+        let i = self.prompt.selected.unwrap_or(0);
+        if let Some(s) = self.prompt.suggestions.get(i) {
+            self.prompt.query = s.label.clone();
+            self.prompt.selected = Some(i);
+        }
+    }
+
+    /// fetches the selected suggestion, or the query as typed, and
+    /// shows it in place of the current location
+    pub fn prompt_submit(&mut self) -> io::Result<()> {
+        // what goes in the history: the place's name rather than the
+        // coordinates it is fetched by, and nothing for saved places
+        let (location, history) = match self.prompt.selected.and_then(|i| self.prompt.suggestions.get(i)) {
+            Some(s) => {
+                let history = match s.source {
+                    Source::Place => None,
+                    Source::History => Some(s.location.clone()),
+                    Source::Provider => Some(s.label.clone()),
+                };
+                (s.location.clone(), history)
+            }
+            None => {
+                let q = self.prompt.query.trim().to_string();
+                if q.is_empty() {
+                    return self.close_prompt();
+                }
+                let history = (!q.starts_with('@')).then(|| q.clone());
+                match resolve_location(q, &self.args.places) {
+                    Ok(loc) => (loc, history),
+                    Err(e) => {
+                        self.prompt.message = Some(e);
+                        return self.util_clear_screen();
+                    }
+                }
+            }
+        };
+
+        // fetching takes a moment, say so first
+        self.prompt.message = Some(format!("Loading {location}…"));
+        self.draw_prompt()?;
+        self.sout.flush()?;
+
+        match api_main(
+            &self.args.provider,
+            &self.args.key_source,
+            &location,
+            &self.args.forecast,
+            self.args.max_age,
+        ) {
+            Ok(w) => {
+                if let Some(h) = history {
+                    add_to_history(&h);
+                }
+                self.weather = w;
+                self.args.location = location;
+                self.selected_day = 0;
+                self.precipitation.clear();
+                self.close_prompt()
+            }
+            Err(e) => {
+                self.prompt.message = Some(e);
+                self.util_clear_screen()
+            }
+        }
+    }
+
+    /// asks the provider once the query has settled. errors are shown
+    /// under the prompt instead of ending the program; search_main
+    /// keeps the key out of them. the request blocks the loop, so keys
+    /// typed meanwhile wait until it is answered.
+    pub fn prompt_search(&mut self) -> io::Result<()> {
+        let q = self.prompt.query.trim().to_string();
+        if q.chars().count() < MIN_SEARCH_LEN
+            || q == self.prompt.searched
+            || q.starts_with('@')
+            || self.prompt.last_edit.elapsed() < SEARCH_DELAY
+        {
+            return Ok(());
+        }
+        self.prompt.searched = q.clone();
+        // input waits for the answer, say so first
+        self.prompt.message = Some(format!("Searching for {q}…"));
+        self.draw_prompt()?;
+        self.sout.flush()?;
+        self.prompt.message = None;
+        self.prompt.found = match search_main(&self.args.provider, &self.args.key_source, &q) {
+            Ok(results) => results
+                .into_iter()
+                .map(|r| Suggestion {
+                    label: [r.name.as_str(), r.region.as_str(), r.country.as_str()]
+                        .into_iter()
+                        .filter(|p| !p.is_empty())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    location: format!("{},{}", r.lat, r.lon),
+                    source: Source::Provider,
+                })
+                .collect(),
+            Err(e) => {
+                self.prompt.message = Some(e);
+                Vec::new()
+            }
+        };
+        self.update_suggestions();
+        self.util_clear_screen()
+    }
+
+    /// saved places and history matching the query, then whatever the
+    /// provider found for it
+    fn update_suggestions(&mut self) {
+        let q = self.prompt.query.trim().to_lowercase();
+        let q = q.trim_start_matches('@');
+        let matches = |s: &str| s.to_lowercase().contains(q);
+
+        let mut s: Vec<Suggestion> = self
+            .args
+            .places
+            .iter()
+            .filter(|(name, loc)| matches(name) || matches(loc))
+            .map(|(name, loc)| Suggestion {
+                label: format!("@{name}"),
+                location: loc.clone(),
+                source: Source::Place,
+            })
+            .collect();
+        s.extend(load_history().into_iter().filter(|l| matches(l)).map(|l| Suggestion {
+            label: l.clone(),
+            location: l,
+            source: Source::History,
+        }));
+        if self.prompt.searched.eq_ignore_ascii_case(self.prompt.query.trim()) {
+            s.extend(self.prompt.found.iter().cloned());
+        }
+        s.truncate(MAX_SUGGESTIONS);
+        self.prompt.suggestions = s;
+    }
+
+    /// the prompt in a box in the middle of the screen
+    pub fn draw_prompt(&mut self) -> io::Result<()> {
+        let p = &self.prompt;
+        let mut lines = vec![format!("/ {}█", p.query), String::new()];
+        for (i, s) in p.suggestions.iter().enumerate() {
+            let marker = match p.selected == Some(i) {
+                true => '▸',
+                false => ' ',
+            };
+            lines.push(format!("{marker} {:<7} {}", s.source.name(), s.label));
+        }
+        if let Some(m) = &p.message {
+            lines.push(String::new());
+            lines.push(m.clone());
+        }
+
+        let width = self.columns.min(MAX_PROMPT_WIDTH);
+        let height = (lines.len() as u16 + 2).min(self.view_rows());
+        let rect = Rect {
+            col: (self.columns - width) / 2,
+            row: (self.view_rows() - height) / 3,
+            width,
+            height,
+        };
+        self.w_panel("Location", rect, &lines, true)
+    }
+}
@@ -0,0 +1,455 @@
+use std::{fmt::Write, io};
+
+use chrono::{DateTime, NaiveTime, Utc};
+
+use crate::{
+    Regn,
+    api::{
+        api_main::{CurrentCondition, parse_current_weather},
+        request::{RespAlert, RespForecastDay},
+    },
+    datetime::{DisplayZone, fmt_utc_offset},
+    export::write_textfile,
+    theme::{condition_rgb, hex},
+};
+
+/// documents `regn report` can write
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+/// one row of the forecast table, already in the chosen units
+struct ReportDay {
+    label: String,
+    condition: CurrentCondition,
+    text: String,
+    max: String,
+    min: String,
+    rain: i32,
+    wind: String,
+    precip: String,
+}
+
+/// one row of the astronomy table
+struct ReportAstro {
+    label: String,
+    sunrise: String,
+    sunset: String,
+    moonrise: String,
+    moonset: String,
+    moon_phase: String,
+}
+
+impl Regn {
+    /// `regn report --markdown|--html [-o <path>]`
+    pub fn print_report(&self) -> io::Result<()> {
+        let doc = match self.args.report {
+            Some(ReportFormat::Html) => self.html_report(),
+            _ => self.markdown_report(),
+        };
+        match &self.args.out_path {
+            Some(path) => write_textfile(path, &doc).map_err(io::Error::other),
+            None => {
+                print!("{doc}");
+                Ok(())
+            }
+        }
+    }
+
+    fn report_title(&self) -> String {
+        format!(
+            "Weather for {}, {}",
+            self.weather.location.name, self.weather.location.country
+        )
+    }
+
+    /// the location's local time when the data was reported
+    fn report_time(&self) -> String {
+        let w = &self.weather;
+        let (t, off) = DisplayZone::Location.wall_clock(w.now, &w.zone);
+        format!(
+            "{} ({}, {})",
+            self.args.time_format.date_time(t),
+            fmt_utc_offset(off),
+            w.zone.tz_id
+        )
+    }
+
+    fn report_current(&self) -> [String; 3] {
+        let u = self.args.units;
+        let w = &self.weather;
+        [
+            format!("{}, {}", u.temp(w.current_temp_c), w.current_condition_as_str.trim()),
+            format!(
+                "Feels like {}, wind {}, humidity {}%",
+                u.temp(w.current_feelslike_c),
+                u.wind(w.current_wind_kph),
+                w.current_humidity
+            ),
+            format!(
+                "Precip. {}, pressure {}",
+                u.precip(w.current_precip_mm),
+                u.pressure(w.current_pressure_mb)
+            ),
+        ]
+    }
+
+    fn report_days(&self) -> Vec<ReportDay> {
+        let u = self.args.units;
+        let today = self.weather.location.localtime.date();
+        self.weather
+            .forecast_days
+            .iter()
+            .map(|d| {
+                let text = d.day.condition.text.trim().to_string();
+                ReportDay {
+                    label: self.args.time_format.day_label(d.date, today),
+                    condition: parse_current_weather(text.clone()),
+                    text,
+                    max: u.temp(d.day.maxtemp_c),
+                    min: u.temp(d.day.mintemp_c),
+                    rain: d.day.daily_chance_of_rain,
+                    wind: u.wind(d.day.maxwind_kph),
+                    precip: u.precip(d.day.totalprecip_mm),
+                }
+            })
+            .collect()
+    }
+
+    fn report_astro(&self) -> Vec<ReportAstro> {
+        let today = self.weather.location.localtime.date();
+        let at = |d: &RespForecastDay, t: Option<NaiveTime>| match t {
+            Some(t) => self.args.time_format.time(self.display_time(d.date, t).0.time()),
+            None => "none".to_string(),
+        };
+        self.weather
+            .forecast_days
+            .iter()
+            .map(|d| ReportAstro {
+                label: self.args.time_format.day_label(d.date, today),
+                sunrise: at(d, d.astro.sunrise),
+                sunset: at(d, d.astro.sunset),
+                moonrise: at(d, d.astro.moonrise),
+                moonset: at(d, d.astro.moonset),
+                moon_phase: d.astro.moon_phase.clone(),
+            })
+            .collect()
+    }
+
+    /// the UTC offset of the astronomy times, as they are shown in the
+    /// chosen display timezone
+    fn astro_offset(&self) -> Option<String> {
+        let d = self.weather.forecast_days.first()?;
+        let t = d.astro.sunrise?;
+        Some(fmt_utc_offset(self.display_time(d.date, t).1))
+    }
+
+    /// an alert's start or end in the chosen display timezone, or the
+    /// text as the provider sent it if it can't be read
+    fn alert_time(&self, s: &str) -> String {
+        match DateTime::parse_from_rfc3339(s) {
+            Ok(t) => {
+                let (wall, off) = self
+                    .args
+                    .time_zone
+                    .wall_clock(t.with_timezone(&Utc), &self.weather.zone);
+                format!("{} ({})", self.args.time_format.date_time(wall), fmt_utc_offset(off))
+            }
+            Err(_) => s.to_string(),
+        }
+    }
+
+    fn alert_meta(&self, a: &RespAlert) -> String {
+        [&a.severity, &a.event, &a.areas]
+            .into_iter()
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| s.trim())
+            .collect::<Vec<_>>()
+            .join(" · ")
+    }
+
+    fn report_footer(&self) -> String {
+        let fetched = self.weather.fetched_at;
+        let (t, off) = self.args.time_zone.wall_clock(fetched, &self.weather.zone);
+        format!(
+            "Data from {}, fetched {} ({}).",
+            self.args.provider.title(),
+            self.args.time_format.date_time(t),
+            fmt_utc_offset(off)
+        )
+    }
+
+    pub fn markdown_report(&self) -> String {
+        let mut s = String::new();
+        let _ = writeln!(s, "# {}\n", md_escape(&self.report_title()));
+        let _ = writeln!(s, "{}\n", md_escape(&self.report_time()));
+
+        let [now, feels, precip] = self.report_current();
+        let _ = writeln!(s, "## Now\n");
+        let _ = writeln!(s, "{} {}  ", self.weather.current_condition.icon(), md_escape(&now));
+        let _ = writeln!(s, "{}  ", md_escape(&feels));
+        let _ = writeln!(s, "{}\n", md_escape(&precip));
+
+        let _ = writeln!(s, "## Forecast\n");
+        let _ = writeln!(s, "| Day | | Max | Min | Rain | Wind | Precip. | Conditions |");
+        let _ = writeln!(s, "|---|:-:|--:|--:|--:|--:|--:|---|");
+        for d in self.report_days() {
+            let _ = writeln!(
+                s,
+                "| {} | {} | {} | {} | {}% | {} | {} | {} |",
+                md_escape(&d.label),
+                d.condition.icon(),
+                d.max,
+                d.min,
+                d.rain,
+                d.wind,
+                d.precip,
+                md_escape(&d.text)
+            );
+        }
+        s.push('\n');
+
+        let _ = writeln!(s, "## Alerts\n");
+        if self.weather.alerts.is_empty() {
+            let _ = writeln!(s, "No alerts.\n");
+        }
+        for a in self.weather.alerts.iter() {
+            let _ = writeln!(s, "### {}\n", md_escape(a.headline.trim()));
+            let meta = self.alert_meta(a);
+            if !meta.is_empty() {
+                let _ = writeln!(s, "**{}**  ", md_escape(&meta));
+            }
+            let _ = writeln!(
+                s,
+                "From {} until {}\n",
+                md_escape(&self.alert_time(&a.effective)),
+                md_escape(&self.alert_time(&a.expires))
+            );
+            for text in [&a.desc, &a.instruction] {
+                if !text.trim().is_empty() {
+                    let _ = writeln!(s, "{}\n", md_escape(text.trim()));
+                }
+            }
+        }
+
+        let _ = writeln!(s, "## Astronomy\n");
+        if let Some(off) = self.astro_offset() {
+            let _ = writeln!(s, "Times are {off}.\n");
+        }
+        let _ = writeln!(s, "| Day | Sunrise | Sunset | Moonrise | Moonset | Moon |");
+        let _ = writeln!(s, "|---|---|---|---|---|---|");
+        for a in self.report_astro() {
+            let _ = writeln!(
+                s,
+                "| {} | {} | {} | {} | {} | {} |",
+                md_escape(&a.label),
+                md_escape(&a.sunrise),
+                md_escape(&a.sunset),
+                md_escape(&a.moonrise),
+                md_escape(&a.moonset),
+                md_escape(&a.moon_phase)
+            );
+        }
+        let _ = writeln!(s, "\n_{}_", md_escape(&self.report_footer()));
+        s
+    }
+
+    pub fn html_report(&self) -> String {
+        let mut s = String::new();
+        let title = html_escape(&self.report_title());
+        let _ = writeln!(s, "<!DOCTYPE html>");
+        let _ = writeln!(s, "<html lang=\"en\">");
+        let _ = writeln!(s, "<head>");
+        let _ = writeln!(s, "<meta charset=\"utf-8\">");
+        let _ = writeln!(s, "<title>{title}</title>");
+        let _ = writeln!(s, "<style>{HTML_STYLE}</style>");
+        let _ = writeln!(s, "</head>");
+        let _ = writeln!(s, "<body>");
+        let _ = writeln!(s, "<h1>{title}</h1>");
+        let _ = writeln!(s, "<p class=\"time\">{}</p>", html_escape(&self.report_time()));
+
+        let [now, feels, precip] = self.report_current();
+        let _ = writeln!(s, "<h2>Now</h2>");
+        let _ = writeln!(s, "<div class=\"now\">");
+        let _ = writeln!(s, "{}", svg_icon(self.weather.current_condition, 64));
+        let _ = writeln!(
+            s,
+            "<p><strong>{}</strong><br>{}<br>{}</p>",
+            html_escape(&now),
+            html_escape(&feels),
+            html_escape(&precip)
+        );
+        let _ = writeln!(s, "</div>");
+
+        let _ = writeln!(s, "<h2>Forecast</h2>");
+        let _ = writeln!(s, "<table>");
+        let _ = writeln!(
+            s,
+            "<tr><th>Day</th><th></th><th>Max</th><th>Min</th><th>Rain</th><th>Wind</th><th>Precip.</th><th>Conditions</th></tr>"
+        );
+        for d in self.report_days() {
+            let _ = writeln!(
+                s,
+                "<tr><td>{}</td><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}%</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td>{}</td></tr>",
+                html_escape(&d.label),
+                svg_icon(d.condition, 24),
+                html_escape(&d.max),
+                html_escape(&d.min),
+                d.rain,
+                html_escape(&d.wind),
+                html_escape(&d.precip),
+                html_escape(&d.text)
+            );
+        }
+        let _ = writeln!(s, "</table>");
+
+        let _ = writeln!(s, "<h2>Alerts</h2>");
+        if self.weather.alerts.is_empty() {
+            let _ = writeln!(s, "<p>No alerts.</p>");
+        }
+        for a in self.weather.alerts.iter() {
+            let _ = writeln!(s, "<div class=\"alert\">");
+            let _ = writeln!(s, "<h3>{}</h3>", html_escape(a.headline.trim()));
+            let meta = self.alert_meta(a);
+            if !meta.is_empty() {
+                let _ = writeln!(s, "<p><strong>{}</strong></p>", html_escape(&meta));
+            }
+            let _ = writeln!(
+                s,
+                "<p>From {} until {}</p>",
+                html_escape(&self.alert_time(&a.effective)),
+                html_escape(&self.alert_time(&a.expires))
+            );
+            for text in [&a.desc, &a.instruction] {
+                if !text.trim().is_empty() {
+                    let _ = writeln!(s, "<p>{}</p>", html_escape(text.trim()));
+                }
+            }
+            let _ = writeln!(s, "</div>");
+        }
+
+        let _ = writeln!(s, "<h2>Astronomy</h2>");
+        if let Some(off) = self.astro_offset() {
+            let _ = writeln!(s, "<p>Times are {}.</p>", html_escape(&off));
+        }
+        let _ = writeln!(s, "<table>");
+        let _ = writeln!(
+            s,
+            "<tr><th>Day</th><th>Sunrise</th><th>Sunset</th><th>Moonrise</th><th>Moonset</th><th>Moon</th></tr>"
+        );
+        for a in self.report_astro() {
+            let _ = writeln!(
+                s,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                html_escape(&a.label),
+                html_escape(&a.sunrise),
+                html_escape(&a.sunset),
+                html_escape(&a.moonrise),
+                html_escape(&a.moonset),
+                html_escape(&a.moon_phase)
+            );
+        }
+        let _ = writeln!(s, "</table>");
+        let _ = writeln!(s, "<p class=\"footer\">{}</p>", html_escape(&self.report_footer()));
+        let _ = writeln!(s, "</body>");
+        let _ = writeln!(s, "</html>");
+        s
+    }
+}
+
+/// kept small, so the page still looks fine when pasted into a wiki
+const HTML_STYLE: &str = "body{font-family:sans-serif;max-width:52em;margin:2em auto;padding:0 1em;color:#222}\
+table{border-collapse:collapse}\
+th,td{padding:.3em .7em;border-bottom:1px solid #ddd;text-align:left;vertical-align:middle}\
+td.num{text-align:right}\
+.now{display:flex;align-items:center;gap:1em}\
+.alert{border-left:4px solid #e6a23c;padding:0 1em;margin-bottom:1em}\
+.time,.footer{color:#666}";
+
+/// a drawing of the condition, coloured like the bars and text output
+pub fn svg_icon(c: CurrentCondition, size: u32) -> String {
+    let color = hex(condition_rgb(c));
+    let grey = hex(condition_rgb(CurrentCondition::Cloud));
+    let cloud = |fill: &str, dy: i32| {
+        format!(
+            "<path transform=\"translate(0 {dy})\" d=\"M20 46h26a10 10 0 0 0 0-20 14 14 0 0 0-27-3A11 11 0 0 0 20 46z\" fill=\"{fill}\"/>"
+        )
+    };
+    let body = match c {
+        CurrentCondition::Sun => format!(
+            "<circle cx=\"32\" cy=\"32\" r=\"12\" fill=\"{color}\"/><path d=\"M32 6v8M32 50v8M6 32h8M50 32h8M13.6 13.6l5.7 5.7M44.7 44.7l5.7 5.7M13.6 50.4l5.7-5.7M44.7 19.3l5.7-5.7\" stroke=\"{color}\" stroke-width=\"4\" stroke-linecap=\"round\"/>"
+        ),
+        CurrentCondition::Clear => {
+            format!("<path d=\"M40 10a22 22 0 1 0 14 34A18 18 0 0 1 40 10z\" fill=\"{color}\"/>")
+        }
+        CurrentCondition::Cloud => cloud(&color, 0),
+        CurrentCondition::Rain => format!(
+            "{}<path d=\"M24 48l-3 8M34 48l-3 8M44 48l-3 8\" stroke=\"{color}\" stroke-width=\"4\" stroke-linecap=\"round\"/>",
+            cloud(&grey, -8)
+        ),
+        CurrentCondition::Snow => format!(
+            "{}<g fill=\"{color}\" stroke=\"{grey}\"><circle cx=\"22\" cy=\"50\" r=\"3\"/><circle cx=\"32\" cy=\"56\" r=\"3\"/><circle cx=\"42\" cy=\"50\" r=\"3\"/></g>",
+            cloud(&grey, -8)
+        ),
+        CurrentCondition::Thunder => format!(
+            "{}<path d=\"M34 38l-8 12h7l-4 10 11-14h-7l4-8z\" fill=\"{color}\"/>",
+            cloud(&grey, -8)
+        ),
+        CurrentCondition::Fog => format!(
+            "<path d=\"M12 24h40M8 34h48M14 44h36\" stroke=\"{color}\" stroke-width=\"5\" stroke-linecap=\"round\"/>"
+        ),
+        CurrentCondition::Unknown => format!(
+            "<circle cx=\"32\" cy=\"32\" r=\"22\" fill=\"none\" stroke=\"{color}\" stroke-width=\"4\"/><text x=\"32\" y=\"42\" font-size=\"28\" font-family=\"sans-serif\" text-anchor=\"middle\" fill=\"{color}\">?</text>"
+        ),
+    };
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{size}\" height=\"{size}\" viewBox=\"0 0 64 64\" role=\"img\" aria-label=\"{name}\"><title>{name}</title>{body}</svg>",
+        name = c.name()
+    )
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// escapes the characters markdown would read as formatting or as a
+/// table cell boundary
+fn md_escape(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.chars() {
+        if matches!(c, '\\' | '|' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '#') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn html_escaping() {
+        assert_eq!(html_escape("Sunny"), "Sunny");
+        assert_eq!(
+            html_escape("<script>alert(\"x\")</script>"),
+            "&lt;script&gt;alert(&quot;x&quot;)&lt;/script&gt;"
+        );
+        assert_eq!(html_escape("Wind & rain &lt;"), "Wind &amp; rain &amp;lt;");
+    }
+
+    #[test]
+    fn md_escaping() {
+        assert_eq!(md_escape("Partly cloudy"), "Partly cloudy");
+        assert_eq!(md_escape("a | b"), "a \\| b");
+        assert_eq!(md_escape("*heavy* _snow_"), "\\*heavy\\* \\_snow\\_");
+        assert_eq!(md_escape("# [x](y) `z` \\"), "\\# \\[x\\](y) \\`z\\` \\\\");
+        assert_eq!(md_escape("<b>"), "\\<b\\>");
+    }
+}
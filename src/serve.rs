@@ -0,0 +1,81 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    time::Duration,
+};
+
+/// a client that is this slow is dropped, so it can't hold up others
+const TIMEOUT: Duration = Duration::from_secs(5);
+/// most bytes read of the request line and headers together
+const MAX_REQUEST: u64 = 8192;
+
+/// ":9101" listens on all interfaces, "127.0.0.1:9101" on one
+pub fn parse_listen_addr(s: &str) -> Option<SocketAddr> {
+    match s.strip_prefix(':') {
+        Some(port) => format!("0.0.0.0:{port}").parse().ok(),
+        None => s.parse().ok(),
+    }
+}
+
+/// a minimal HTTP server answering GET `path` with a freshly rendered
+/// body. requests are handled one at a time, which is plenty for
+/// a scraper or a calendar app polling now and then, as long as no
+/// client gets to keep the server waiting for long.
+pub fn serve(
+    addr: SocketAddr,
+    path: &str,
+    content_type: &str,
+    render: impl Fn() -> Result<String, String>,
+) -> Result<(), String> {
+    let listener =
+        TcpListener::bind(addr).map_err(|e| format!("Could not listen on {addr}: {e}"))?;
+    eprintln!("Serving http://{addr}{path}");
+
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        if let Err(e) = answer(stream, path, content_type, &render) {
+            eprintln!("WARNING: {e}");
+        }
+    }
+    Ok(())
+}
+
+fn answer(
+    mut stream: TcpStream,
+    path: &str,
+    content_type: &str,
+    render: &impl Fn() -> Result<String, String>,
+) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new((&stream).take(MAX_REQUEST));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // the headers are not needed, but have to be read before answering
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    // query strings are ignored, calendar apps like to add them
+    let target = target.split('?').next().unwrap_or("");
+
+    let (status, content_type, body) = match (method, target == path) {
+        ("GET", true) => match render() {
+            Ok(body) => ("200 OK", content_type, body),
+            Err(e) => ("500 Internal Server Error", "text/plain", format!("{e}\n")),
+        },
+        ("GET", false) => ("404 Not Found", "text/plain", format!("Try {path}\n")),
+        _ => ("405 Method Not Allowed", "text/plain", String::new()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
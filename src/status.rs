@@ -0,0 +1,91 @@
+use std::io;
+
+use chrono::Utc;
+use crossterm::{
+    QueueableCommand,
+    cursor::MoveTo,
+    style::{Color, Print, SetForegroundColor},
+};
+
+use crate::{ProgState, Regn, controls::KEYS, layout::Rect};
+
+/// key hints at the end of the status line, dropped first when it is
+/// too narrow
+const HINTS: &str = "? help  / location  Esc quit";
+
+impl Regn {
+    /// rows left above the status line
+    pub fn view_rows(&self) -> u16 {
+        self.rows.saturating_sub(1)
+    }
+
+    /// how long ago the shown weather was fetched
+    pub fn data_age(&self) -> String {
+        let mins = (Utc::now() - self.weather.fetched_at).num_minutes().max(0);
+        match mins {
+            0 => "just now".to_string(),
+            1..60 => format!("{mins} min ago"),
+            _ => format!("{}h {}min ago", mins / 60, mins % 60),
+        }
+    }
+
+    /// location, provider and the age of the data on the left, the main
+    /// keys on the right, on the last row
+    pub fn status_line(&mut self) -> io::Result<()> {
+        if self.rows < 2 {
+            return Ok(());
+        }
+        let w = &self.weather;
+        let (updated, _) = self.args.time_zone.wall_clock(w.fetched_at, &w.zone);
+        let info = format!(
+            " {}, {} · {} · updated {} ({})",
+            w.location.name,
+            w.location.country,
+            self.args.provider.title(),
+            self.args.time_format.time(updated.time()),
+            self.data_age()
+        );
+        let width = self.columns as usize;
+        let used = info.chars().count() + HINTS.chars().count() + 2;
+        let line = match used <= width {
+            true => format!("{info}{}{HINTS} ", " ".repeat(width - used + 1)),
+            false => info.chars().take(width).collect(),
+        };
+
+        self.sout.queue(MoveTo(0, self.rows - 1))?;
+        self.sout.queue(SetForegroundColor(self.args.theme.frame_color()))?;
+        self.sout.queue(Print(format!("{line:<width$}")))?;
+        self.sout.queue(SetForegroundColor(Color::Reset))?;
+        Ok(())
+    }
+
+    pub fn toggle_help(&mut self) -> io::Result<()> {
+        self.prog_state = match self.prog_state {
+            ProgState::Help => ProgState::Main,
+            _ => ProgState::Help,
+        };
+        self.util_clear_screen()
+    }
+
+    /// every key binding, in a box over the panels
+    pub fn draw_help(&mut self) -> io::Result<()> {
+        let key_w = KEYS.iter().map(|(k, _)| k.chars().count()).max().unwrap_or(0);
+        let mut lines: Vec<String> = KEYS
+            .iter()
+            .map(|(keys, what)| format!("{keys:<key_w$}  {what}"))
+            .collect();
+        lines.push(String::new());
+        lines.push("Press any key to close".to_string());
+
+        let width = (lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) as u16 + 4)
+            .min(self.columns);
+        let height = (lines.len() as u16 + 2).min(self.view_rows());
+        let rect = Rect {
+            col: (self.columns - width) / 2,
+            row: (self.view_rows() - height) / 2,
+            width,
+            height,
+        };
+        self.w_panel("Keys", rect, &lines, true)
+    }
+}
@@ -0,0 +1,552 @@
+use crate::{Regn, api::api_main::parse_current_weather};
+
+/// a parsed --format template, e.g. "{icon} {temp}° {cond} ↑{max} ↓{min}"
+///
+/// {field}            a value, numbers in the chosen units without suffix
+/// {field.N}          the value for forecast day N (0 is today)
+/// {field:u}          append the unit, e.g. "14.2°C"
+/// {field:>6.0}       pad to 6 columns (< left, > right, ^ center),
+///                    0 decimals
+/// {?field>5}..{:}..{/}   text shown if the test passes, optional else
+/// {{ and }}          literal braces
+#[derive(PartialEq, Clone, Debug)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+enum Part {
+    Text(String),
+    Value(FieldRef, Spec),
+    If {
+        test: Test,
+        then: Vec<Part>,
+        otherwise: Vec<Part>,
+    },
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+enum Field {
+    Temp,
+    Feels,
+    Wind,
+    Precip,
+    Pressure,
+    Cond,
+    Icon,
+    Code,
+    Location,
+    Country,
+    Time,
+    Date,
+    Day,
+    Max,
+    Min,
+    Rain,
+    Sunrise,
+    Sunset,
+}
+
+const FIELDS: &[(&str, Field)] = &[
+    ("temp", Field::Temp),
+    ("feels", Field::Feels),
+    ("wind", Field::Wind),
+    ("precip", Field::Precip),
+    ("pressure", Field::Pressure),
+    ("cond", Field::Cond),
+    ("icon", Field::Icon),
+    ("code", Field::Code),
+    ("location", Field::Location),
+    ("country", Field::Country),
+    ("time", Field::Time),
+    ("date", Field::Date),
+    ("day", Field::Day),
+    ("max", Field::Max),
+    ("min", Field::Min),
+    ("rain", Field::Rain),
+    ("sunrise", Field::Sunrise),
+    ("sunset", Field::Sunset),
+];
+
+/// fields that describe the current conditions when not given a day
+const CURRENT_FIELDS: &[Field] = &[
+    Field::Temp,
+    Field::Feels,
+    Field::Wind,
+    Field::Precip,
+    Field::Pressure,
+    Field::Cond,
+    Field::Icon,
+    Field::Code,
+];
+
+/// fields that can only be about now
+const NOW_ONLY_FIELDS: &[Field] = &[
+    Field::Temp,
+    Field::Feels,
+    Field::Pressure,
+    Field::Location,
+    Field::Country,
+    Field::Time,
+];
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+struct FieldRef {
+    field: Field,
+    day: Option<usize>,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+struct Spec {
+    unit: bool,
+    align: Option<Align>,
+    width: usize,
+    precision: Option<usize>,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+struct Test {
+    field: FieldRef,
+    op: Option<(Op, String)>,
+}
+
+/// a rendered value before formatting
+enum Value {
+    Num(f64, &'static str),
+    Text(String),
+}
+
+/// a conditional being parsed, or the whole template at the bottom
+struct Frame {
+    test: Option<Test>,
+    then: Vec<Part>,
+    otherwise: Option<Vec<Part>>,
+}
+
+impl Frame {
+    fn parts(&mut self) -> &mut Vec<Part> {
+        match &mut self.otherwise {
+            Some(o) => o,
+            None => &mut self.then,
+        }
+    }
+}
+
+impl Template {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut chars = s.chars().peekable();
+        let mut stack = vec![Frame {
+            test: None,
+            then: Vec::new(),
+            otherwise: None,
+        }];
+        let mut text = String::new();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '}' => return Err("unmatched \"}\", write \"}}\" for a brace".to_string()),
+                '{' => {
+                    let mut tag = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => tag.push(c),
+                            None => return Err(format!("\"{{{tag}\" is never closed")),
+                        }
+                    }
+                    // the stack always has the bottom frame
+                    let top = stack.len() - 1;
+                    if !text.is_empty() {
+                        stack[top].parts().push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    match tag.as_str() {
+                        ":" => match &mut stack[top] {
+                            Frame {
+                                test: Some(_),
+                                otherwise: o @ None,
+                                ..
+                            } => *o = Some(Vec::new()),
+                            _ => return Err("\"{:}\" outside of a \"{?...}\"".to_string()),
+                        },
+                        "/" => {
+                            let Some(Frame {
+                                test: Some(test),
+                                then,
+                                otherwise,
+                            }) = stack.pop().filter(|f| f.test.is_some())
+                            else {
+                                return Err("\"{/}\" without a \"{?...}\"".to_string());
+                            };
+                            stack[top - 1].parts().push(Part::If {
+                                test,
+                                then,
+                                otherwise: otherwise.unwrap_or_default(),
+                            });
+                        }
+                        t if t.starts_with('?') => stack.push(Frame {
+                            test: Some(parse_test(&t[1..])?),
+                            then: Vec::new(),
+                            otherwise: None,
+                        }),
+                        t => {
+                            let (name, spec) = t.split_once(':').unwrap_or((t, ""));
+                            let part = Part::Value(parse_field(name)?, parse_spec(spec)?);
+                            stack[top].parts().push(part);
+                        }
+                    }
+                }
+                c => text.push(c),
+            }
+        }
+        if stack.len() > 1 {
+            return Err("a \"{?...}\" is missing its \"{/}\"".to_string());
+        }
+        let mut root = stack.remove(0);
+        if !text.is_empty() {
+            root.then.push(Part::Text(text));
+        }
+        Ok(Self { parts: root.then })
+    }
+
+    /// how many forecast days the template refers to
+    pub fn days_needed(&self) -> usize {
+        fn max_day(parts: &[Part]) -> usize {
+            parts
+                .iter()
+                .map(|p| match p {
+                    Part::Text(_) => 0,
+                    Part::Value(f, _) => f.day.map_or(0, |d| d + 1),
+                    Part::If {
+                        test,
+                        then,
+                        otherwise,
+                    } => test
+                        .field
+                        .day
+                        .map_or(0, |d| d + 1)
+                        .max(max_day(then))
+                        .max(max_day(otherwise)),
+                })
+                .max()
+                .unwrap_or(0)
+        }
+        max_day(&self.parts)
+    }
+}
+
+fn parse_field(s: &str) -> Result<FieldRef, String> {
+    let (name, day) = match s.trim().split_once('.') {
+        Some((n, d)) => {
+            let day = d
+                .parse::<usize>()
+                .map_err(|_| format!("\"{d}\" in \"{s}\" is not a day number"))?;
+            (n, Some(day))
+        }
+        None => (s.trim(), None),
+    };
+    let field = FIELDS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, f)| *f)
+        .ok_or_else(|| {
+            let names: Vec<&str> = FIELDS.iter().map(|(n, _)| *n).collect();
+            format!("unknown field \"{name}\" (fields: {})", names.join(", "))
+        })?;
+    if day.is_some() && NOW_ONLY_FIELDS.contains(&field) {
+        return Err(format!("\"{name}\" has no per-day value"));
+    }
+    Ok(FieldRef { field, day })
+}
+
+/// widest padding and most decimals a spec may ask for
+const MAX_SPEC: usize = 255;
+
+/// "u", "<10", ">6.1", "u^8"
+fn parse_spec(s: &str) -> Result<Spec, String> {
+    let mut spec = Spec::default();
+    let mut rest = s;
+    if let Some(r) = rest.strip_prefix('u') {
+        spec.unit = true;
+        rest = r;
+    }
+    spec.align = match rest.chars().next() {
+        Some('<') => Some(Align::Left),
+        Some('>') => Some(Align::Right),
+        Some('^') => Some(Align::Center),
+        _ => None,
+    };
+    if spec.align.is_some() {
+        rest = &rest[1..];
+    }
+    let (width, precision) = rest.split_once('.').unwrap_or((rest, ""));
+    let bad = || format!("\"{s}\" is not a valid format spec (e.g. u, <10, >6.1)");
+    if !width.is_empty() {
+        spec.width = width.parse().map_err(|_| bad())?;
+    }
+    if rest.contains('.') {
+        spec.precision = Some(precision.parse().map_err(|_| bad())?);
+    }
+    if spec.width > MAX_SPEC || spec.precision.is_some_and(|p| p > MAX_SPEC) {
+        return Err(bad());
+    }
+    Ok(spec)
+}
+
+fn parse_test(s: &str) -> Result<Test, String> {
+    // two-character operators first so ">=" isn't read as ">"
+    let ops = [
+        (">=", Op::Ge),
+        ("<=", Op::Le),
+        ("!=", Op::Ne),
+        (">", Op::Gt),
+        ("<", Op::Lt),
+        ("=", Op::Eq),
+    ];
+    for (sym, op) in ops {
+        if let Some((field, value)) = s.split_once(sym) {
+            return Ok(Test {
+                field: parse_field(field)?,
+                op: Some((op, value.trim().to_string())),
+            });
+        }
+    }
+    Ok(Test {
+        field: parse_field(s)?,
+        op: None,
+    })
+}
+
+impl Regn {
+    pub fn render_template(&self, t: &Template) -> String {
+        let mut s = String::new();
+        self.render_parts(&t.parts, &mut s);
+        s
+    }
+
+    fn render_parts(&self, parts: &[Part], out: &mut String) {
+        for p in parts {
+            match p {
+                Part::Text(t) => out.push_str(t),
+                Part::Value(f, spec) => {
+                    if let Some(v) = self.template_value(f) {
+                        out.push_str(&format_value(v, spec));
+                    }
+                }
+                Part::If {
+                    test,
+                    then,
+                    otherwise,
+                } => match self.passes(test) {
+                    true => self.render_parts(then, out),
+                    false => self.render_parts(otherwise, out),
+                },
+            }
+        }
+    }
+
+    fn passes(&self, test: &Test) -> bool {
+        let Some(v) = self.template_value(&test.field) else {
+            return false;
+        };
+        let Some((op, rhs)) = &test.op else {
+            return match v {
+                Value::Num(n, _) => n != 0.0,
+                Value::Text(t) => !t.is_empty(),
+            };
+        };
+        let ord = match (&v, rhs.parse::<f64>()) {
+            (Value::Num(n, _), Ok(r)) => n.partial_cmp(&r),
+            (Value::Num(n, _), Err(_)) => Some(round_1(*n).to_string().as_str().cmp(rhs)),
+            (Value::Text(t), _) => Some(t.to_lowercase().as_str().cmp(&rhs.to_lowercase())),
+        };
+        let Some(ord) = ord else {
+            return false;
+        };
+        match op {
+            Op::Eq => ord.is_eq(),
+            Op::Ne => ord.is_ne(),
+            Op::Lt => ord.is_lt(),
+            Op::Le => ord.is_le(),
+            Op::Gt => ord.is_gt(),
+            Op::Ge => ord.is_ge(),
+        }
+    }
+
+    /// None for days that weren't returned by the provider
+    fn template_value(&self, f: &FieldRef) -> Option<Value> {
+        let w = &self.weather;
+        let u = self.args.units;
+        let tf = &self.args.time_format;
+        let temp = |c: f64| Value::Num(u.temp.convert(c), u.temp.suffix());
+        let wind = |kmh: f64| Value::Num(u.wind.convert(kmh), u.wind.suffix());
+        let precip = |mm: f64| Value::Num(u.precip.convert(mm), u.precip.suffix());
+
+        if f.day.is_none() && CURRENT_FIELDS.contains(&f.field) {
+            return Some(match f.field {
+                Field::Temp => temp(w.current_temp_c),
+                Field::Feels => temp(w.current_feelslike_c),
+                Field::Wind => wind(w.current_wind_kph),
+                Field::Precip => precip(w.current_precip_mm),
+                Field::Pressure => Value::Num(
+                    u.pressure.convert(w.current_pressure_mb),
+                    u.pressure.suffix(),
+                ),
+                Field::Cond => Value::Text(w.current_condition_as_str.trim().to_string()),
+                Field::Icon => Value::Text(w.current_condition.icon().to_string()),
+                _ => Value::Text(w.current_condition.name().to_string()),
+            });
+        }
+
+        match f.field {
+            Field::Location => return Some(Value::Text(w.location.name.clone())),
+            Field::Country => return Some(Value::Text(w.location.country.clone())),
+            Field::Time => {
+                let (wall, _) = self.args.time_zone.wall_clock(w.now, &w.zone);
+                return Some(Value::Text(tf.time(wall.time())));
+            }
+            _ => {}
+        }
+
+        let d = w.forecast_days.get(f.day.unwrap_or(0))?;
+        let astro = |t| {
+            Value::Text(match t {
+                Some(t) => tf.time(self.display_time(d.date, t).0.time()),
+                None => String::new(),
+            })
+        };
+        let condition = || parse_current_weather(d.day.condition.text.clone());
+        Some(match f.field {
+            Field::Max => temp(d.day.maxtemp_c),
+            Field::Min => temp(d.day.mintemp_c),
+            Field::Wind => wind(d.day.maxwind_kph),
+            Field::Precip => precip(d.day.totalprecip_mm),
+            Field::Rain => Value::Num(d.day.daily_chance_of_rain as f64, "%"),
+            Field::Cond => Value::Text(d.day.condition.text.trim().to_string()),
+            Field::Icon => Value::Text(condition().icon().to_string()),
+            Field::Code => Value::Text(condition().name().to_string()),
+            Field::Date => Value::Text(tf.date(d.date)),
+            Field::Day => Value::Text(tf.day_label(d.date, w.location.localtime.date())),
+            Field::Sunrise => astro(d.astro.sunrise),
+            _ => astro(d.astro.sunset),
+        })
+    }
+}
+
+fn format_value(v: Value, spec: &Spec) -> String {
+    let (s, numeric) = match v {
+        Value::Num(n, suffix) => {
+            let mut s = match spec.precision {
+                Some(p) => format!("{n:.p$}"),
+                None => round_1(n).to_string(),
+            };
+            if spec.unit {
+                s.push_str(suffix);
+            }
+            (s, true)
+        }
+        Value::Text(t) => (t, false),
+    };
+
+    let len = s.chars().count();
+    if len >= spec.width {
+        return s;
+    }
+    let pad = spec.width - len;
+    // numbers line up on the right unless told otherwise
+    let align = spec.align.unwrap_or(match numeric {
+        true => Align::Right,
+        false => Align::Left,
+    });
+    match align {
+        Align::Left => format!("{s}{}", " ".repeat(pad)),
+        Align::Right => format!("{}{s}", " ".repeat(pad)),
+        Align::Center => format!(
+            "{}{s}{}",
+            " ".repeat(pad / 2),
+            " ".repeat(pad - pad / 2)
+        ),
+    }
+}
+
+fn round_1(v: f64) -> f64 {
+    (v * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn specs() {
+        assert_eq!(
+            parse_spec("u>6.1"),
+            Ok(Spec {
+                unit: true,
+                align: Some(Align::Right),
+                width: 6,
+                precision: Some(1),
+            })
+        );
+        assert_eq!(parse_spec(""), Ok(Spec::default()));
+        assert!(parse_spec("x").is_err());
+        assert!(parse_spec(">6.x").is_err());
+    }
+
+    #[test]
+    fn spec_limits() {
+        assert!(parse_spec("255.255").is_ok());
+        assert!(parse_spec(".99999").is_err());
+        assert!(parse_spec(">9999999999").is_err());
+        assert!(Template::parse("{temp:.99999}").is_err());
+    }
+
+    #[test]
+    fn parse_errors() {
+        for t in [
+            "{temp",
+            "temp}",
+            "{nope}",
+            "{temp.x}",
+            "{temp.1}",
+            "{?wind>5}windy",
+            "{:}",
+            "{/}",
+            "{?wind}a{:}b{:}c{/}",
+        ] {
+            assert!(Template::parse(t).is_err(), "{t}");
+        }
+        assert!(Template::parse("{{temp}} {temp:u} {?wind>5}windy{:}calm{/}").is_ok());
+    }
+
+    #[test]
+    fn days_needed() {
+        assert_eq!(Template::parse("{temp}").unwrap().days_needed(), 0);
+        assert_eq!(Template::parse("{wind.2}").unwrap().days_needed(), 3);
+        assert_eq!(Template::parse("{?cond.4=Sunny}sun{/}").unwrap().days_needed(), 5);
+    }
+}
@@ -0,0 +1,138 @@
+use crossterm::style::Color;
+
+use crate::api::api_main::CurrentCondition;
+
+/// colours used for the frame, its text and the weather animations
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Theme {
+    Default,
+    Mono,
+    Ocean,
+}
+
+impl Theme {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "default" => Some(Self::Default),
+            "mono" => Some(Self::Mono),
+            "ocean" => Some(Self::Ocean),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Mono => "mono",
+            Self::Ocean => "ocean",
+        }
+    }
+
+    pub fn frame_color(&self) -> Color {
+        match self {
+            Self::Default | Self::Mono => Color::Reset,
+            Self::Ocean => Color::Cyan,
+        }
+    }
+
+    /// the border of the focused panel
+    pub fn focus_color(&self) -> Color {
+        match self {
+            Self::Default => Color::Yellow,
+            Self::Mono => Color::Reset,
+            Self::Ocean => Color::White,
+        }
+    }
+
+    /// temperature, feels-like and the "now" marker in the graph
+    pub fn graph_colors(&self) -> (Color, Color, Color) {
+        match self {
+            Self::Default => (Color::Yellow, Color::Cyan, Color::Red),
+            Self::Mono => (Color::Reset, Color::DarkGrey, Color::Reset),
+            Self::Ocean => (Color::Cyan, Color::Blue, Color::White),
+        }
+    }
+
+    pub fn particle_colors(&self) -> Vec<Color> {
+        match self {
+            Self::Default => {
+                vec![Color::DarkGrey, Color::Grey, Color::Reset, Color::White]
+            }
+            Self::Mono => vec![Color::Reset],
+            Self::Ocean => {
+                vec![Color::Blue, Color::DarkBlue, Color::Cyan, Color::DarkCyan]
+            }
+        }
+    }
+}
+
+/// a colour per weather condition, for the bars and coloured text
+pub fn condition_rgb(c: CurrentCondition) -> (u8, u8, u8) {
+    match c {
+        CurrentCondition::Sun => (0xf9, 0xd7, 0x4a),
+        CurrentCondition::Clear => (0xc8, 0xc8, 0xe6),
+        CurrentCondition::Cloud => (0xb4, 0xbe, 0xc8),
+        CurrentCondition::Rain => (0x5a, 0x9b, 0xe6),
+        CurrentCondition::Snow => (0xe6, 0xf0, 0xff),
+        CurrentCondition::Fog => (0x96, 0x96, 0x96),
+        CurrentCondition::Thunder => (0xc8, 0x78, 0xf0),
+        CurrentCondition::Unknown => (0xdc, 0xdc, 0xdc),
+    }
+}
+
+/// the colour in 24 bits when the terminal says it can show them
+/// (COLORTERM), else the closest of the 256 standard colours
+pub fn term_color((r, g, b): (u8, u8, u8)) -> Color {
+    let truecolor = std::env::var("COLORTERM").is_ok_and(|v| v == "truecolor" || v == "24bit");
+    match truecolor {
+        true => Color::Rgb { r, g, b },
+        false => Color::AnsiValue(ansi_256((r, g, b))),
+    }
+}
+
+/// the closest colour of the 6x6x6 cube in the 256-colour palette
+fn ansi_256((r, g, b): (u8, u8, u8)) -> u8 {
+    // the cube's levels are 0, 95, 135, 175, 215 and 255
+    let level = |v: u8| match v {
+        0..48 => 0,
+        48..115 => 1,
+        v => (v - 35) / 40,
+    };
+    16 + 36 * level(r) + 6 * level(g) + level(b)
+}
+
+/// "#f9d74a"
+pub fn hex(rgb: (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", rgb.0, rgb.1, rgb.2)
+}
+
+/// colder is bluer, warmer goes through green and yellow to red
+pub fn temp_rgb(celsius: f64) -> (u8, u8, u8) {
+    const GRADES: &[(f64, (u8, u8, u8))] = &[
+        (-10.0, (0x5f, 0x87, 0xff)),
+        (0.0, (0x87, 0xaf, 0xff)),
+        (5.0, (0x87, 0xd7, 0xff)),
+        (10.0, (0x87, 0xff, 0xd7)),
+        (15.0, (0xaf, 0xff, 0x87)),
+        (20.0, (0xd7, 0xff, 0x5f)),
+        (25.0, (0xff, 0xd7, 0x5f)),
+        (30.0, (0xff, 0xaf, 0x5f)),
+    ];
+    GRADES
+        .iter()
+        .find(|(max, _)| celsius < *max)
+        .map_or((0xff, 0x5f, 0x5f), |(_, rgb)| *rgb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closest_256_colour() {
+        assert_eq!(ansi_256((0, 0, 0)), 16);
+        assert_eq!(ansi_256((0xff, 0xff, 0xff)), 231);
+        assert_eq!(ansi_256((0xff, 0x5f, 0x5f)), 203);
+        assert_eq!(ansi_256((0x87, 0xaf, 0xff)), 111);
+    }
+}
@@ -0,0 +1,312 @@
+/// preset unit systems selectable with --units
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum UnitSystem {
+    Metric,
+    Imperial,
+    Uk,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum TempUnit {
+    Celsius,
+    Fahrenheit,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum WindUnit {
+    Kmh,
+    Ms,
+    Mph,
+    Knots,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum PrecipUnit {
+    Mm,
+    In,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum PressureUnit {
+    Hpa,
+    InHg,
+}
+
+/// the unit used for each quantity when presenting weather data.
+/// all values coming from the API are kept in metric and converted here.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct Units {
+    pub temp: TempUnit,
+    pub wind: WindUnit,
+    pub precip: PrecipUnit,
+    pub pressure: PressureUnit,
+}
+
+impl UnitSystem {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "metric" => Some(Self::Metric),
+            "imperial" | "us" => Some(Self::Imperial),
+            "uk" => Some(Self::Uk),
+            _ => None,
+        }
+    }
+}
+
+impl TempUnit {
+    /// the canonical name, as accepted by parse()
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Celsius => "c",
+            Self::Fahrenheit => "f",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "c" | "celsius" => Some(Self::Celsius),
+            "f" | "fahrenheit" => Some(Self::Fahrenheit),
+            _ => None,
+        }
+    }
+
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            Self::Celsius => "°C",
+            Self::Fahrenheit => "°F",
+        }
+    }
+
+    pub fn convert(&self, celsius: f64) -> f64 {
+        match self {
+            Self::Celsius => celsius,
+            Self::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+        }
+    }
+}
+
+impl WindUnit {
+    /// the canonical name, as accepted by parse()
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Kmh => "kmh",
+            Self::Ms => "ms",
+            Self::Mph => "mph",
+            Self::Knots => "kn",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "kmh" | "km/h" | "kph" => Some(Self::Kmh),
+            "ms" | "m/s" => Some(Self::Ms),
+            "mph" => Some(Self::Mph),
+            "kn" | "kt" | "knots" => Some(Self::Knots),
+            _ => None,
+        }
+    }
+
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            Self::Kmh => " km/h",
+            Self::Ms => " m/s",
+            Self::Mph => " mph",
+            Self::Knots => " kn",
+        }
+    }
+
+    pub fn convert(&self, kmh: f64) -> f64 {
+        match self {
+            Self::Kmh => kmh,
+            Self::Ms => kmh / 3.6,
+            Self::Mph => kmh / 1.609344,
+            Self::Knots => kmh / 1.852,
+        }
+    }
+}
+
+impl PrecipUnit {
+    /// the canonical name, as accepted by parse()
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Mm => "mm",
+            Self::In => "in",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "mm" => Some(Self::Mm),
+            "in" | "inch" => Some(Self::In),
+            _ => None,
+        }
+    }
+
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            Self::Mm => " mm",
+            Self::In => " in",
+        }
+    }
+
+    pub fn convert(&self, mm: f64) -> f64 {
+        match self {
+            Self::Mm => mm,
+            Self::In => mm / 25.4,
+        }
+    }
+}
+
+impl PressureUnit {
+    /// the canonical name, as accepted by parse()
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Hpa => "hpa",
+            Self::InHg => "inhg",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "hpa" | "mb" | "mbar" => Some(Self::Hpa),
+            "inhg" => Some(Self::InHg),
+            _ => None,
+        }
+    }
+
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            Self::Hpa => " hPa",
+            Self::InHg => " inHg",
+        }
+    }
+
+    pub fn convert(&self, hpa: f64) -> f64 {
+        match self {
+            Self::Hpa => hpa,
+            Self::InHg => hpa * 0.02953,
+        }
+    }
+}
+
+impl Units {
+    pub fn from_system(system: UnitSystem) -> Self {
+        match system {
+            UnitSystem::Metric => Self {
+                temp: TempUnit::Celsius,
+                wind: WindUnit::Kmh,
+                precip: PrecipUnit::Mm,
+                pressure: PressureUnit::Hpa,
+            },
+            UnitSystem::Imperial => Self {
+                temp: TempUnit::Fahrenheit,
+                wind: WindUnit::Mph,
+                precip: PrecipUnit::In,
+                pressure: PressureUnit::InHg,
+            },
+            UnitSystem::Uk => Self {
+                temp: TempUnit::Celsius,
+                wind: WindUnit::Mph,
+                precip: PrecipUnit::Mm,
+                pressure: PressureUnit::Hpa,
+            },
+        }
+    }
+
+    pub fn temp(&self, celsius: f64) -> String {
+        format!("{}{}", round_1(self.temp.convert(celsius)), self.temp.suffix())
+    }
+
+    pub fn wind(&self, kmh: f64) -> String {
+        format!("{}{}", round_1(self.wind.convert(kmh)), self.wind.suffix())
+    }
+
+    /// the temperature as a bare number, for tables
+    pub fn temp_value(&self, celsius: f64) -> f64 {
+        round_1(self.temp.convert(celsius))
+    }
+
+    pub fn wind_value(&self, kmh: f64) -> f64 {
+        round_1(self.wind.convert(kmh))
+    }
+
+    pub fn precip(&self, mm: f64) -> String {
+        // inches need an extra decimal to be of any use
+        let v = match self.precip {
+            PrecipUnit::Mm => round_1(self.precip.convert(mm)),
+            PrecipUnit::In => (self.precip.convert(mm) * 100.0).round() / 100.0,
+        };
+        format!("{}{}", v, self.precip.suffix())
+    }
+
+    pub fn pressure(&self, hpa: f64) -> String {
+        let v = match self.pressure {
+            PressureUnit::Hpa => self.pressure.convert(hpa).round(),
+            PressureUnit::InHg => {
+                (self.pressure.convert(hpa) * 100.0).round() / 100.0
+            }
+        };
+        format!("{}{}", v, self.pressure.suffix())
+    }
+}
+
+impl Default for Units {
+    fn default() -> Self {
+        Self::from_system(UnitSystem::Metric)
+    }
+}
+
+fn round_1(v: f64) -> f64 {
+    (v * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn systems() {
+        let imperial = Units::from_system(UnitSystem::Imperial);
+        assert_eq!(imperial.temp(20.0), "68°F");
+        assert_eq!(imperial.temp(-40.0), "-40°F");
+        assert_eq!(imperial.wind(100.0), "62.1 mph");
+        assert_eq!(imperial.precip(10.0), "0.39 in");
+        assert_eq!(imperial.pressure(1013.25), "29.92 inHg");
+
+        let metric = Units::default();
+        assert_eq!(metric.temp(21.04), "21°C");
+        assert_eq!(metric.precip(1.25), "1.3 mm");
+        assert_eq!(metric.pressure(1013.25), "1013 hPa");
+
+        let uk = Units::from_system(UnitSystem::Uk);
+        assert_eq!(uk.temp(20.0), "20°C");
+        assert_eq!(uk.wind(100.0), "62.1 mph");
+    }
+
+    #[test]
+    fn wind_units() {
+        let wind = |w| Units { wind: w, ..Units::default() };
+        assert_eq!(wind(WindUnit::Ms).wind(36.0), "10 m/s");
+        assert_eq!(wind(WindUnit::Knots).wind(100.0), "54 kn");
+        assert_eq!(wind(WindUnit::Knots).wind_value(100.0), 54.0);
+    }
+
+    #[test]
+    fn names_parse_back() {
+        for t in [TempUnit::Celsius, TempUnit::Fahrenheit] {
+            assert_eq!(TempUnit::parse(t.name()), Some(t));
+        }
+        for w in [WindUnit::Kmh, WindUnit::Ms, WindUnit::Mph, WindUnit::Knots] {
+            assert_eq!(WindUnit::parse(w.name()), Some(w));
+        }
+        for p in [PrecipUnit::Mm, PrecipUnit::In] {
+            assert_eq!(PrecipUnit::parse(p.name()), Some(p));
+        }
+        for p in [PressureUnit::Hpa, PressureUnit::InHg] {
+            assert_eq!(PressureUnit::parse(p.name()), Some(p));
+        }
+        assert_eq!(UnitSystem::parse("US"), Some(UnitSystem::Imperial));
+        assert_eq!(WindUnit::parse("km/h"), Some(WindUnit::Kmh));
+        assert_eq!(TempUnit::parse("kelvin"), None);
+    }
+}
@@ -1,4 +1,4 @@
-use std::{io, time::Duration};
+use std::{fmt::Display, io, time::Duration};
 
 use crossterm::{
     ExecutableCommand, QueueableCommand, cursor,
@@ -8,7 +8,7 @@ use crossterm::{
     },
 };
 
-use crate::Regn;
+use crate::{Regn, help::APP_NAME};
 
 impl Regn {
     pub fn util_clear_screen(&mut self) -> io::Result<()> {
@@ -37,3 +37,9 @@ impl Regn {
 pub fn get_fps(fps: f64) -> Duration {
     Duration::from_secs_f64(1.0 / fps)
 }
+
+/// prints the error and exits with the given code
+pub fn exit_with_error(e: &impl Display, code: i32) -> ! {
+    eprintln!("{APP_NAME}: error: {e}");
+    std::process::exit(code)
+}